@@ -1,10 +1,14 @@
 // arduino.rs
 
-// 사용되지 않는 코드에 대한 경고를 비활성화 (모든 핀을 항상 사용하는 것은 아니므로)
-#![allow(dead_code)]
+// 사용되지 않는 코드에 대한 경고를 비활성화 (모든 핀을 항상 사용하는 것은 아니므로, 테스트 빌드에서는 확인)
+#![cfg_attr(not(test), allow(dead_code))]
+
+use core::convert::Infallible;
+
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin, StatefulOutputPin};
 
 // port.rs 모듈을 가져옵니다.
-use crate::port;
+use crate::port::{self, RegisterAccess};
 
 /// 핀 모드를 나타내는 열거형 (입력, 출력, 풀업 입력)
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -251,19 +255,24 @@ fn get_mapping(arduino_pin_number: u8) -> &'static ArduinoPinMapping {
 /// `pin_number`가 유효하지 않으면 패닉합니다.
 pub fn pin_mode(pin_number: u8, mode: PinMode) {
     let mapping = get_mapping(pin_number);
+    apply_pin_mode(mapping.port, mapping.pin_on_port, mode);
+}
+
+/// `port`의 `pin_on_port`번 비트에 핀 모드를 적용합니다. (`pin_mode` 본체, 테스트에서 mock 포트로 호출)
+fn apply_pin_mode<R: RegisterAccess>(port: &port::Port<R>, pin_on_port: u8, mode: PinMode) {
     match mode {
         PinMode::Output => {
-            mapping.port.set_pin_output(mapping.pin_on_port);
+            port.set_pin_output(pin_on_port);
         }
         PinMode::Input => {
-            mapping.port.set_pin_input(mapping.pin_on_port);
+            port.set_pin_input(pin_on_port);
             // 일반 입력 모드에서는 풀업 저항을 비활성화합니다 (PORTx 비트를 0으로 설정).
-            mapping.port.set_pin_low(mapping.pin_on_port);
+            port.set_pin_low(pin_on_port);
         }
         PinMode::InputPullup => {
-            mapping.port.set_pin_input(mapping.pin_on_port);
+            port.set_pin_input(pin_on_port);
             // 입력 모드로 설정 후, 내부 풀업 저항을 활성화합니다 (PORTx 비트를 1로 설정).
-            mapping.port.set_pin_high(mapping.pin_on_port);
+            port.set_pin_high(pin_on_port);
         }
     }
 }
@@ -318,3 +327,212 @@ pub fn digital_toggle(pin_number: u8) {
     mapping.port.toggle_pin(mapping.pin_on_port);
 }
 
+/// embedded-hal 1.0 트레이트(`OutputPin`, `StatefulOutputPin`, `InputPin`)를 구현하는 아두이노 핀
+///
+/// 생태계 드라이버에 아두이노 핀 번호로 지정한 핀을 넘길 때 사용합니다.
+/// 모드 검사는 하지 않으므로 `pin_mode`와 같이 올바른 모드로 만들어 사용하세요.
+///
+/// ```ignore
+/// let mut led = ArduinoPin::new(LED_BUILTIN, PinMode::Output);
+/// led.set_high()?;
+/// ```
+pub struct ArduinoPin {
+    mapping: &'static ArduinoPinMapping,
+}
+
+impl ArduinoPin {
+    /// 핀 모드를 설정하고 핀을 만듭니다.
+    ///
+    /// # Panics
+    /// `pin_number`가 유효하지 않으면 패닉합니다.
+    pub fn new(pin_number: u8, mode: PinMode) -> Self {
+        pin_mode(pin_number, mode);
+        ArduinoPin { mapping: get_mapping(pin_number) }
+    }
+}
+
+impl ErrorType for ArduinoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for ArduinoPin {
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.mapping.port.set_pin_high(self.mapping.pin_on_port);
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.mapping.port.set_pin_low(self.mapping.pin_on_port);
+        Ok(())
+    }
+}
+
+impl StatefulOutputPin for ArduinoPin {
+    fn is_set_high(&mut self) -> Result<bool, Infallible> {
+        Ok(self.mapping.port.read_output_pin(self.mapping.pin_on_port))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Infallible> {
+        Ok(!self.mapping.port.read_output_pin(self.mapping.pin_on_port))
+    }
+
+    fn toggle(&mut self) -> Result<(), Infallible> {
+        self.mapping.port.toggle_pin(self.mapping.pin_on_port);
+        Ok(())
+    }
+}
+
+impl InputPin for ArduinoPin {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(self.mapping.port.read_pin(self.mapping.pin_on_port))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(!self.mapping.port.read_pin(self.mapping.pin_on_port))
+    }
+}
+
+//
+// 호스트 시뮬레이션 테스트 (`cargo test-host`): 핀 번호 → 포트 레지스터 매핑
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::sim;
+
+    // 가상 레지스터 주소 (port.rs 참고)
+    const PINB: usize = 0x23;
+    const DDRB: usize = 0x24;
+    const PORTB: usize = 0x25;
+    const DDRF: usize = 0x30;
+    const DDRH: usize = 0x101;
+
+    fn setup() -> std::sync::MutexGuard<'static, ()> {
+        let guard = sim::lock();
+        sim::reset();
+        guard
+    }
+
+    #[test]
+    fn led_builtin_is_portb_bit7() {
+        let _guard = setup();
+
+        pin_mode(LED_BUILTIN, PinMode::Output);
+        assert_eq!(sim::register(DDRB), 1 << 7);
+
+        digital_write(D13, PinState::High);
+        assert_eq!(sim::register(PORTB), 1 << 7);
+        digital_toggle(D13);
+        assert_eq!(sim::register(PORTB), 0);
+    }
+
+    #[test]
+    fn input_modes_control_the_pull_up() {
+        let _guard = setup();
+
+        pin_mode(D13, PinMode::Output);
+        pin_mode(D13, PinMode::InputPullup);
+        assert_eq!(sim::register(DDRB), 0);
+        assert_eq!(sim::register(PORTB), 1 << 7);
+
+        pin_mode(D13, PinMode::Input);
+        assert_eq!(sim::register(PORTB), 0);
+    }
+
+    #[test]
+    fn digital_read_uses_pin_register() {
+        let _guard = setup();
+
+        sim::set_register(PINB, 1 << 7);
+        assert_eq!(digital_read(D13), PinState::High);
+        sim::set_register(PINB, !(1 << 7));
+        assert_eq!(digital_read(D13), PinState::Low);
+    }
+
+    #[test]
+    fn analog_and_extended_ports_are_mapped() {
+        let _guard = setup();
+
+        // A0 = PF0, D6 = PH3 (0x100 이상 확장 I/O 영역)
+        pin_mode(A0, PinMode::Output);
+        pin_mode(D6, PinMode::Output);
+
+        assert_eq!(sim::register(DDRF), 1 << 0);
+        assert_eq!(sim::register(DDRH), 1 << 3);
+    }
+
+    #[test]
+    fn arduino_pin_implements_embedded_hal() {
+        let _guard = setup();
+
+        let mut led = ArduinoPin::new(LED_BUILTIN, PinMode::Output);
+        assert_eq!(sim::register(DDRB), 1 << 7);
+        led.set_high().unwrap();
+        assert_eq!(sim::register(PORTB), 1 << 7);
+        assert!(led.is_set_high().unwrap());
+        led.toggle().unwrap();
+        assert!(led.is_set_low().unwrap());
+
+        let mut button = ArduinoPin::new(D12, PinMode::InputPullup);
+        assert_eq!(sim::register(PORTB), 1 << 6);
+        sim::set_register(PINB, 0);
+        assert!(button.is_low().unwrap());
+    }
+
+    #[test]
+    fn every_pin_constant_maps_to_a_distinct_port_bit() {
+        let pins = [
+            D0, D1, D2, D3, D4, D5, D6, D7, D8, D9, D10, D11, D12, D13,
+            D14, D15, D16, D17, D18, D19, D20, D21,
+            D22, D23, D24, D25, D26, D27, D28, D29,
+            D30, D31, D32, D33, D34, D35, D36, D37,
+            D38, D39, D40, D41, D42, D43, D44, D45, D46, D47, D48, D49,
+            D50, D51, D52, D53,
+            A0, A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15,
+        ];
+        assert!(pins.iter().copied().eq(0..TOTAL_MAPPED_PINS as u8));
+
+        // 같은 포트 비트를 두 핀 번호가 가리키지 않음
+        let mut used = std::collections::BTreeSet::new();
+        for pin in pins {
+            let mapping = get_mapping(pin);
+            assert!(mapping.pin_on_port < 8);
+            assert!(used.insert((mapping.port.port_addr(), mapping.pin_on_port)), "D{pin} is mapped twice");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid Arduino pin number")]
+    fn unknown_pin_panics() {
+        let _guard = setup();
+        pin_mode(200, PinMode::Output);
+    }
+
+    //
+    // 레지스터 접근 기록 mock으로 확인 (가상 레지스터 파일 대신 어떤 접근을 했는지 검사)
+    //
+    #[test]
+    fn pin_mode_output_sets_ddrb_bit7() {
+        use port::mock::{RecordingRegisters, RegisterOp::*};
+
+        let regs = RecordingRegisters::new();
+        let mapping = get_mapping(D13);
+        apply_pin_mode(&mapping.port.with_registers(&regs), mapping.pin_on_port, PinMode::Output);
+
+        assert_eq!(regs.take_log(), [SetBit(DDRB, 7)]);
+        assert_eq!(regs.value(DDRB), 1 << 7);
+    }
+
+    #[test]
+    fn pin_mode_input_clears_the_pull_up() {
+        use port::mock::{RecordingRegisters, RegisterOp::*};
+
+        let regs = RecordingRegisters::new();
+        regs.set_value(PORTB, 0xFF);
+        let mapping = get_mapping(D13);
+        apply_pin_mode(&mapping.port.with_registers(&regs), mapping.pin_on_port, PinMode::Input);
+
+        assert_eq!(regs.take_log(), [ClearBit(DDRB, 7), ClearBit(PORTB, 7)]);
+        assert_eq!(regs.value(PORTB), 0x7F);
+    }
+}
@@ -0,0 +1,464 @@
+
+//
+// 스케줄러 위에서 동작하는 협력형 async/await executor
+//
+// - no_std, 힙 없음: future는 고정 크기 정적 풀(TASK_POOL)에 직접 저장
+// - Waker는 태스크 번호를 담고 있으며, wake 시 READY 비트만 세움 (ISR에서 호출 가능)
+// - `Timer`는 스케줄러 tick(ms가 바뀔 때)에서 `on_tick()`으로 깨어남
+//
+// 사용 예: `executor::init()`으로 executor를 스케줄러의 이벤트 태스크로 등록한 뒤
+//          `executor::spawn(my_async_fn())`으로 async 태스크를 띄웁니다.
+//          wake가 일어날 때만 executor 태스크가 notify되므로, 할 일이 없으면 스케줄러가
+//          idle 슬립에 들어갈 수 있습니다.
+//
+#![cfg_attr(not(test), allow(dead_code))]
+
+use core::cell::{RefCell, UnsafeCell};
+use core::future::Future;
+use core::mem::{align_of, size_of, MaybeUninit};
+use core::pin::Pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use crate::hal::interrupt::{self, CriticalSection, Mutex};
+
+use crate::scheduler::{self, time_reached, SchedulerError, TaskHandle};
+
+//
+// 풀 설정
+//
+/// 동시에 존재할 수 있는 async 태스크 수 (READY 비트마스크 폭과 같아야 함)
+const MAX_ASYNC_TASKS: usize = 8;
+/// async 태스크 하나(future 상태 머신)가 차지할 수 있는 최대 바이트 수
+const TASK_STORAGE_SIZE: usize = 128;
+/// 동시에 대기할 수 있는 `Timer` 수
+const MAX_TIMERS: usize = MAX_ASYNC_TASKS;
+
+/// spawn 실패 원인
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpawnError {
+    /// 태스크 풀에 빈 슬롯이 없음
+    PoolFull,
+}
+
+//
+// future 저장 공간
+//
+#[repr(C, align(8))]
+struct TaskStorage(UnsafeCell<MaybeUninit<[u8; TASK_STORAGE_SIZE]>>);
+
+struct TaskPool([TaskStorage; MAX_ASYNC_TASKS]);
+
+// 각 슬롯은 POLL_FNS에 등록된 동안 executor(run)만 접근하며,
+// spawn은 비어 있는 슬롯에만 쓰므로 동시에 같은 슬롯을 건드리지 않습니다.
+unsafe impl Sync for TaskPool {}
+
+impl TaskPool {
+    fn slot_ptr(&self, index: usize) -> *mut u8 {
+        self.0[index].0.get() as *mut u8
+    }
+}
+
+static TASK_POOL: TaskPool =
+    TaskPool([const { TaskStorage(UnsafeCell::new(MaybeUninit::uninit())) }; MAX_ASYNC_TASKS]);
+
+/// 저장된 future를 한 번 poll하고, 완료되면 drop한 뒤 `true`를 반환
+type PollFn = unsafe fn(*mut u8, &mut Context<'_>) -> bool;
+
+/// 슬롯별 poll 함수 (None이면 빈 슬롯)
+static POLL_FNS: Mutex<RefCell<[Option<PollFn>; MAX_ASYNC_TASKS]>> =
+    Mutex::new(RefCell::new([None; MAX_ASYNC_TASKS]));
+
+/// poll이 필요한 태스크 비트마스크 (bit n = 슬롯 n)
+static READY: Mutex<RefCell<u8>> = Mutex::new(RefCell::new(0));
+
+/// executor를 실행하는 스케줄러 이벤트 태스크
+static EXECUTOR_TASK: Mutex<RefCell<Option<TaskHandle>>> = Mutex::new(RefCell::new(None));
+
+/// executor를 스케줄러 이벤트 태스크로 등록합니다.
+///
+/// 등록하지 않고 `scheduler::task_add(executor::run, 0)`처럼 매번 실행되게 해도 동작하지만,
+/// 이 경우 스케줄러가 idle 슬립에 들어가지 못합니다.
+pub fn init() -> Result<TaskHandle, SchedulerError> {
+    let handle = scheduler::task_add_event(run)?;
+    interrupt::free(|cs| {
+        *EXECUTOR_TASK.borrow(cs).borrow_mut() = Some(handle);
+    });
+    // init 전에 spawn된 태스크가 있으면 바로 실행되도록
+    let _ = handle.notify(0);
+    Ok(handle)
+}
+
+unsafe fn poll_task<F: Future<Output = ()>>(ptr: *mut u8, cx: &mut Context<'_>) -> bool {
+    let future = ptr as *mut F;
+    match unsafe { Pin::new_unchecked(&mut *future) }.poll(cx) {
+        Poll::Ready(()) => {
+            unsafe { core::ptr::drop_in_place(future) };
+            true
+        }
+        Poll::Pending => false,
+    }
+}
+
+/// async 태스크를 풀에 등록합니다. 등록 직후 한 번 poll됩니다.
+///
+/// future의 크기가 `TASK_STORAGE_SIZE`를 넘으면 컴파일 에러가 납니다.
+pub fn spawn<F: Future<Output = ()> + 'static>(future: F) -> Result<(), SpawnError> {
+    const {
+        assert!(size_of::<F>() <= TASK_STORAGE_SIZE, "async task is larger than TASK_STORAGE_SIZE");
+        assert!(align_of::<F>() <= align_of::<TaskStorage>(), "async task alignment too large");
+    }
+
+    interrupt::free(|cs| {
+        let mut poll_fns = POLL_FNS.borrow(cs).borrow_mut();
+        let index = poll_fns
+            .iter()
+            .position(|slot| slot.is_none())
+            .ok_or(SpawnError::PoolFull)?;
+
+        unsafe { (TASK_POOL.slot_ptr(index) as *mut F).write(future) };
+        poll_fns[index] = Some(poll_task::<F> as PollFn);
+        mark_ready(cs, index);
+        Ok(())
+    })
+}
+
+/// ready 상태인 async 태스크를 한 번씩 poll합니다.
+///
+/// `init()`이 등록한 스케줄러 태스크가 호출합니다.
+pub fn run() {
+    let ready = interrupt::free(|cs| core::mem::take(&mut *READY.borrow(cs).borrow_mut()));
+
+    for index in 0..MAX_ASYNC_TASKS {
+        if ready & (1 << index) == 0 {
+            continue;
+        }
+        let poll_fn = interrupt::free(|cs| POLL_FNS.borrow(cs).borrow()[index]);
+        let Some(poll_fn) = poll_fn else {
+            continue;
+        };
+
+        let waker = task_waker(index);
+        let mut cx = Context::from_waker(&waker);
+        // 임계구역 밖에서 poll (태스크 내부에서 spawn/wake 가능)
+        if unsafe { poll_fn(TASK_POOL.slot_ptr(index), &mut cx) } {
+            interrupt::free(|cs| POLL_FNS.borrow(cs).borrow_mut()[index] = None);
+        }
+    }
+}
+
+/// 실행 중이거나 대기 중인 async 태스크 수
+pub fn task_count() -> usize {
+    interrupt::free(|cs| POLL_FNS.borrow(cs).borrow().iter().filter(|slot| slot.is_some()).count())
+}
+
+fn mark_ready(cs: CriticalSection, index: usize) {
+    *READY.borrow(cs).borrow_mut() |= 1 << index;
+    if let Some(handle) = *EXECUTOR_TASK.borrow(cs).borrow() {
+        let _ = handle.notify(0);
+    }
+}
+
+//
+// Waker: data 포인터에 태스크 번호를 담음
+//
+static WAKER_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(waker_clone, waker_wake, waker_wake, waker_drop);
+
+fn task_waker(index: usize) -> Waker {
+    unsafe { Waker::from_raw(RawWaker::new(index as *const (), &WAKER_VTABLE)) }
+}
+
+unsafe fn waker_clone(data: *const ()) -> RawWaker {
+    RawWaker::new(data, &WAKER_VTABLE)
+}
+
+unsafe fn waker_wake(data: *const ()) {
+    interrupt::free(|cs| mark_ready(cs, data as usize));
+}
+
+unsafe fn waker_drop(_data: *const ()) {}
+
+//
+// Timer
+//
+/// 등록된 타이머 (`id`로 자신을 등록한 `Timer`를 구분)
+struct TimerEntry {
+    deadline: u32,
+    waker: Waker,
+    id: u16,
+}
+
+const NO_TIMER: Option<TimerEntry> = None;
+static TIMERS: Mutex<RefCell<[Option<TimerEntry>; MAX_TIMERS]>> =
+    Mutex::new(RefCell::new([NO_TIMER; MAX_TIMERS]));
+/// 다음에 부여할 타이머 id (0은 미등록 표시로 사용)
+static NEXT_TIMER_ID: Mutex<RefCell<u16>> = Mutex::new(RefCell::new(1));
+
+/// 스케줄러 tick에서 ms마다 호출: deadline이 지난 타이머의 waker를 깨움
+pub(crate) fn on_tick(cs: CriticalSection, now: u32) {
+    let mut timers = TIMERS.borrow(cs).borrow_mut();
+    for slot in timers.iter_mut() {
+        if matches!(slot, Some(entry) if time_reached(now, entry.deadline))
+            && let Some(entry) = slot.take()
+        {
+            entry.waker.wake();
+        }
+    }
+}
+
+/// 가장 가까운 타이머 deadline (tickless idle에서 사용)
+pub(crate) fn next_deadline(cs: CriticalSection, now: u32) -> Option<u32> {
+    TIMERS
+        .borrow(cs)
+        .borrow()
+        .iter()
+        .flatten()
+        .map(|entry| entry.deadline)
+        .min_by_key(|deadline| deadline.wrapping_sub(now))
+}
+
+/// `id` 타이머의 등록을 지움 (이미 깨어나 지워졌으면 아무것도 하지 않음)
+fn cancel_timer(cs: CriticalSection, id: u16) {
+    let mut timers = TIMERS.borrow(cs).borrow_mut();
+    if let Some(slot) = timers.iter_mut().find(|slot| matches!(slot, Some(entry) if entry.id == id)) {
+        *slot = None;
+    }
+}
+
+/// 지정한 시각까지 기다리는 future (`Timer::after(ms).await`)
+///
+/// 완료 전에 drop되면(예: select에서 다른 쪽이 먼저 끝남) 타이머 슬롯을 돌려줍니다.
+pub struct Timer {
+    deadline: u32,
+    /// 타이머 슬롯 등록 id (0이면 미등록)
+    id: u16,
+}
+
+impl Timer {
+    /// 지금부터 `ms` 뒤
+    pub fn after(ms: u32) -> Self {
+        Self::at(scheduler::millis().wrapping_add(ms))
+    }
+
+    /// 절대 시각 `deadline` (`scheduler::millis()` 기준)
+    pub fn at(deadline: u32) -> Self {
+        Self { deadline, id: 0 }
+    }
+}
+
+impl Future for Timer {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let timer = self.get_mut();
+        interrupt::free(|cs| {
+            if time_reached(scheduler::millis(), timer.deadline) {
+                cancel_timer(cs, core::mem::take(&mut timer.id));
+                return Poll::Ready(());
+            }
+
+            let mut timers = TIMERS.borrow(cs).borrow_mut();
+            // 이 타이머의 기존 등록은 갱신(waker가 바뀌었을 수 있음), 없으면 빈 슬롯 사용
+            let slot = timers
+                .iter()
+                .position(|slot| matches!(slot, Some(entry) if entry.id == timer.id))
+                .or_else(|| timers.iter().position(|slot| slot.is_none()));
+
+            match slot {
+                Some(index) => {
+                    if timers[index].is_none() {
+                        let mut next_id = NEXT_TIMER_ID.borrow(cs).borrow_mut();
+                        timer.id = *next_id;
+                        *next_id = next_id.wrapping_add(1).max(1);
+                    }
+                    timers[index] = Some(TimerEntry {
+                        deadline: timer.deadline,
+                        waker: cx.waker().clone(),
+                        id: timer.id,
+                    });
+                }
+                // 타이머 슬롯이 부족하면 다음 run()에서 다시 poll (바쁜 대기)
+                None => cx.waker().wake_by_ref(),
+            }
+            Poll::Pending
+        })
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if self.id != 0 {
+            interrupt::free(|cs| cancel_timer(cs, self.id));
+        }
+    }
+}
+
+/// 한 번 양보하고 다음 `run()`에서 이어서 실행
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+//
+// 주변장치 ISR용 waker 보관소
+//
+/// ISR에서 async 태스크를 깨우기 위한 waker 슬롯
+///
+/// future는 poll 시 `register()`로 자신의 waker를 등록하고,
+/// ISR은 이벤트 발생 시 `wake()`를 호출합니다.
+pub struct WakerCell(Mutex<RefCell<Option<Waker>>>);
+
+impl WakerCell {
+    pub const fn new() -> Self {
+        Self(Mutex::new(RefCell::new(None)))
+    }
+
+    pub fn register(&self, waker: &Waker) {
+        interrupt::free(|cs| {
+            let mut slot = self.0.borrow(cs).borrow_mut();
+            if !matches!(*slot, Some(ref w) if w.will_wake(waker)) {
+                *slot = Some(waker.clone());
+            }
+        });
+    }
+
+    pub fn wake(&self) {
+        let waker = interrupt::free(|cs| self.0.borrow(cs).borrow_mut().take());
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+//
+// 호스트 시뮬레이션 테스트 (`cargo test-host`)
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::tests::{run_until, setup};
+    use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    /// 스케줄러 초기화 + executor 전역 상태 초기화 (이전 테스트의 future는 버림)
+    fn setup_executor() -> std::sync::MutexGuard<'static, ()> {
+        let guard = setup();
+        interrupt::free(|cs| {
+            *POLL_FNS.borrow(cs).borrow_mut() = [None; MAX_ASYNC_TASKS];
+            *READY.borrow(cs).borrow_mut() = 0;
+            *EXECUTOR_TASK.borrow(cs).borrow_mut() = None;
+            *TIMERS.borrow(cs).borrow_mut() = [NO_TIMER; MAX_TIMERS];
+        });
+        init().unwrap();
+        guard
+    }
+
+    fn timer_slots_in_use() -> usize {
+        interrupt::free(|cs| TIMERS.borrow(cs).borrow().iter().filter(|slot| slot.is_some()).count())
+    }
+
+    #[test]
+    fn spawned_task_runs_to_completion() {
+        static STEPS: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup_executor();
+        STEPS.store(0, Ordering::SeqCst);
+
+        spawn(async {
+            STEPS.fetch_add(1, Ordering::SeqCst);
+            yield_now().await;
+            STEPS.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+        assert_eq!(task_count(), 1);
+        run_until(1);
+
+        assert_eq!(STEPS.load(Ordering::SeqCst), 2);
+        assert_eq!(task_count(), 0);
+    }
+
+    #[test]
+    fn pool_full_is_reported() {
+        let _guard = setup_executor();
+
+        for _ in 0..MAX_ASYNC_TASKS {
+            spawn(core::future::pending::<()>()).unwrap();
+        }
+        assert_eq!(spawn(async {}), Err(SpawnError::PoolFull));
+    }
+
+    #[test]
+    fn timer_wakes_at_its_deadline() {
+        static WOKE_AT: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup_executor();
+        WOKE_AT.store(0, Ordering::SeqCst);
+
+        spawn(async {
+            Timer::after(5).await;
+            WOKE_AT.store(scheduler::millis(), Ordering::SeqCst);
+        })
+        .unwrap();
+        run_until(20);
+
+        assert_eq!(WOKE_AT.load(Ordering::SeqCst), 5);
+        assert_eq!(timer_slots_in_use(), 0);
+    }
+
+    #[test]
+    fn dropped_timer_frees_its_slot() {
+        let _guard = setup_executor();
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut timer = Timer::after(10);
+        assert!(Pin::new(&mut timer).poll(&mut cx).is_pending());
+        // 다시 poll해도 슬롯을 하나만 씀
+        assert!(Pin::new(&mut timer).poll(&mut cx).is_pending());
+        assert_eq!(timer_slots_in_use(), 1);
+
+        drop(timer);
+        assert_eq!(timer_slots_in_use(), 0);
+    }
+
+    #[test]
+    fn waker_cell_wakes_a_waiting_task() {
+        static CELL: WakerCell = WakerCell::new();
+        static EVENT: AtomicBool = AtomicBool::new(false);
+        static DONE: AtomicBool = AtomicBool::new(false);
+        let _guard = setup_executor();
+        EVENT.store(false, Ordering::SeqCst);
+        DONE.store(false, Ordering::SeqCst);
+
+        spawn(async {
+            core::future::poll_fn(|cx| {
+                CELL.register(cx.waker());
+                if EVENT.load(Ordering::SeqCst) { Poll::Ready(()) } else { Poll::Pending }
+            })
+            .await;
+            DONE.store(true, Ordering::SeqCst);
+        })
+        .unwrap();
+        run_until(5);
+        assert!(!DONE.load(Ordering::SeqCst));
+
+        // 주변장치 ISR이 하는 일
+        EVENT.store(true, Ordering::SeqCst);
+        CELL.wake();
+        run_until(6);
+        assert!(DONE.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,378 @@
+
+//
+// 타입 상태(type-state) GPIO 핀
+//
+// 핀의 포트/비트/모드를 타입으로 표현해, 잘못된 사용을 컴파일 에러로 만듭니다.
+//   - 출력이 아닌 핀에 쓰기, 입력이 아닌 핀 읽기 → 메서드가 없어 컴파일 에러
+//   - 같은 포트를 두 번 split → `None` (각 핀 값은 포트당 한 번만 만들어짐)
+//
+// 예)
+//   let portb = gpio::PB::split().unwrap();
+//   let mut led = portb.p7.into_output();         // Pin<PB, 7, Output>
+//   let mut button = portb.p0.into_pull_up_input(); // Pin<PB, 0, Input<PullUp>>
+//   if button.is_low()? { led.set_high()?; }
+//
+// 읽기/쓰기는 embedded-hal 1.0의 `OutputPin` / `StatefulOutputPin` / `InputPin`으로 제공하므로
+// 생태계 드라이버에 핀을 그대로 넘길 수 있습니다. (에러 타입은 `Infallible`)
+//
+// 포트/비트가 타입에 들어 있으므로 방향/출력 비트는 `hal::set_const_bit()` 등으로 바꿔
+// 포트 A~G는 SBI/CBI 한 명령이 됩니다. 읽기/토글은 `port::Port`의 메서드를 그대로 사용합니다.
+// `arduino::pin_mode()` 등 핀 번호를 받는 API와 섞어 쓰면 이 검사를 우회하므로 주의하세요.
+//
+#![cfg_attr(not(test), allow(dead_code))]
+
+use core::cell::Cell;
+use core::convert::Infallible;
+use core::marker::PhantomData;
+
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin, StatefulOutputPin};
+
+use crate::hal::{self, ConstRegister};
+use crate::hal::interrupt::{self, Mutex};
+use crate::port::{self, Port};
+
+//
+// 핀 모드
+//
+/// split 직후 상태 (리셋 상태와 같은 입력이지만, 모드를 정하기 전에는 읽거나 쓸 수 없음)
+pub struct Unconfigured;
+/// 출력
+pub struct Output;
+/// 입력 (`Floating` 또는 `PullUp`)
+pub struct Input<PULL> {
+    _pull: PhantomData<PULL>,
+}
+/// 풀업 없는 입력
+pub struct Floating;
+/// 내부 풀업 입력
+pub struct PullUp;
+
+//
+// 포트
+//
+/// 포트 식별 타입 (`PA` ~ `PL`)
+pub trait PortId {
+    /// 포트 레지스터
+    const PORT: Port;
+    /// split 여부 비트 번호
+    const INDEX: u8;
+}
+
+/// 포트 `P`의 DDRx (상수 주소, `PortId::PORT`에서 가져옴)
+struct Ddr<P>(PhantomData<P>);
+/// 포트 `P`의 PORTx (상수 주소, `PortId::PORT`에서 가져옴)
+struct Out<P>(PhantomData<P>);
+
+impl<P: PortId> ConstRegister for Ddr<P> {
+    const ADDR: usize = P::PORT.ddr_addr();
+}
+
+impl<P: PortId> ConstRegister for Out<P> {
+    const ADDR: usize = P::PORT.port_addr();
+}
+
+/// split된 포트 (비트 n = `PortId::INDEX`)
+static TAKEN: Mutex<Cell<u16>> = Mutex::new(Cell::new(0));
+
+/// 한 포트의 핀 8개
+pub struct Parts<P> {
+    pub p0: Pin<P, 0, Unconfigured>,
+    pub p1: Pin<P, 1, Unconfigured>,
+    pub p2: Pin<P, 2, Unconfigured>,
+    pub p3: Pin<P, 3, Unconfigured>,
+    pub p4: Pin<P, 4, Unconfigured>,
+    pub p5: Pin<P, 5, Unconfigured>,
+    pub p6: Pin<P, 6, Unconfigured>,
+    pub p7: Pin<P, 7, Unconfigured>,
+}
+
+/// 포트를 핀 8개로 나눕니다. 이미 split한 포트면 `None`
+fn split<P: PortId>() -> Option<Parts<P>> {
+    let taken = interrupt::free(|cs| {
+        let taken = TAKEN.borrow(cs);
+        let mask = 1 << P::INDEX;
+        let already = taken.get() & mask != 0;
+        taken.set(taken.get() | mask);
+        already
+    });
+    if taken {
+        return None;
+    }
+    Some(Parts {
+        p0: Pin::new(),
+        p1: Pin::new(),
+        p2: Pin::new(),
+        p3: Pin::new(),
+        p4: Pin::new(),
+        p5: Pin::new(),
+        p6: Pin::new(),
+        p7: Pin::new(),
+    })
+}
+
+macro_rules! ports {
+    ($($id:ident => $port:ident, $index:expr;)*) => {
+        $(
+            #[doc = concat!("`port::", stringify!($port), "` 식별 타입")]
+            pub struct $id;
+
+            impl PortId for $id {
+                const PORT: Port = port::$port;
+                const INDEX: u8 = $index;
+            }
+
+            impl $id {
+                /// 포트를 핀 8개로 나눕니다. 이미 split한 포트면 `None`
+                pub fn split() -> Option<Parts<$id>> {
+                    split::<$id>()
+                }
+            }
+        )*
+    };
+}
+
+ports! {
+    PA => PORTA, 0;
+    PB => PORTB, 1;
+    PC => PORTC, 2;
+    PD => PORTD, 3;
+    PE => PORTE, 4;
+    PF => PORTF, 5;
+    PG => PORTG, 6;
+    PH => PORTH, 7;
+    PJ => PORTJ, 8;
+    PK => PORTK, 9;
+    PL => PORTL, 10;
+}
+
+//
+// 핀
+//
+/// 포트 `P`의 `N`번 비트, 모드 `MODE`인 핀 (크기 0)
+pub struct Pin<P, const N: u8, MODE> {
+    _marker: PhantomData<(P, MODE)>,
+}
+
+impl<P: PortId, const N: u8, MODE> Pin<P, N, MODE> {
+    const fn new() -> Self {
+        const { assert!(N < 8, "pin number must be 0..=7") };
+        Pin { _marker: PhantomData }
+    }
+
+    /// 포트 내 비트 번호
+    pub const fn pin_number(&self) -> u8 {
+        N
+    }
+
+    /// 출력으로 전환 (Low 출력)
+    pub fn into_output(self) -> Pin<P, N, Output> {
+        // 방향을 바꾸기 전에 출력값을 정해 두어 순간적인 High 출력을 막음
+        unsafe {
+            hal::clear_const_bit::<Out<P>, N>();
+            hal::set_const_bit::<Ddr<P>, N>();
+        }
+        Pin::new()
+    }
+
+    /// 출력으로 전환 (High 출력)
+    pub fn into_output_high(self) -> Pin<P, N, Output> {
+        unsafe {
+            hal::set_const_bit::<Out<P>, N>();
+            hal::set_const_bit::<Ddr<P>, N>();
+        }
+        Pin::new()
+    }
+
+    /// 풀업 없는 입력으로 전환
+    pub fn into_floating_input(self) -> Pin<P, N, Input<Floating>> {
+        unsafe {
+            hal::clear_const_bit::<Ddr<P>, N>();
+            hal::clear_const_bit::<Out<P>, N>();
+        }
+        Pin::new()
+    }
+
+    /// 내부 풀업 입력으로 전환
+    pub fn into_pull_up_input(self) -> Pin<P, N, Input<PullUp>> {
+        unsafe {
+            hal::clear_const_bit::<Ddr<P>, N>();
+            hal::set_const_bit::<Out<P>, N>();
+        }
+        Pin::new()
+    }
+}
+
+//
+// embedded-hal 1.0
+//
+impl<P: PortId, const N: u8, MODE> ErrorType for Pin<P, N, MODE> {
+    type Error = Infallible;
+}
+
+impl<P: PortId, const N: u8> OutputPin for Pin<P, N, Output> {
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        unsafe { hal::set_const_bit::<Out<P>, N>() }
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        unsafe { hal::clear_const_bit::<Out<P>, N>() }
+        Ok(())
+    }
+}
+
+impl<P: PortId, const N: u8> StatefulOutputPin for Pin<P, N, Output> {
+    fn is_set_high(&mut self) -> Result<bool, Infallible> {
+        Ok(P::PORT.read_output_pin(N))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Infallible> {
+        Ok(!P::PORT.read_output_pin(N))
+    }
+
+    fn toggle(&mut self) -> Result<(), Infallible> {
+        P::PORT.toggle_pin(N);
+        Ok(())
+    }
+}
+
+impl<P: PortId, const N: u8, PULL> InputPin for Pin<P, N, Input<PULL>> {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(P::PORT.read_pin(N))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(!P::PORT.read_pin(N))
+    }
+}
+
+//
+// 호스트 시뮬레이션 테스트 (`cargo test-host`)
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::sim;
+
+    // 가상 레지스터 주소 (port.rs의 포트 상수에서 가져옴)
+    const PINB: usize = port::PORTB.pin_addr();
+    const DDRB: usize = port::PORTB.ddr_addr();
+    const PORTB: usize = port::PORTB.port_addr();
+    const DDRL: usize = port::PORTL.ddr_addr();
+    const PORTL: usize = port::PORTL.port_addr();
+
+    fn setup() -> std::sync::MutexGuard<'static, ()> {
+        let guard = sim::lock();
+        sim::reset();
+        interrupt::free(|cs| TAKEN.borrow(cs).set(0));
+        guard
+    }
+
+    #[test]
+    fn a_port_can_be_split_only_once() {
+        let _guard = setup();
+
+        assert!(PB::split().is_some());
+        assert!(PB::split().is_none());
+        assert!(PL::split().is_some());
+    }
+
+    #[test]
+    fn output_pin_drives_its_bit() {
+        let _guard = setup();
+
+        let mut led = PB::split().unwrap().p7.into_output();
+        assert_eq!(sim::register(DDRB), 1 << 7);
+        assert_eq!(sim::register(PORTB), 0);
+
+        led.set_high().unwrap();
+        assert_eq!(sim::register(PORTB), 1 << 7);
+        assert!(led.is_set_high().unwrap());
+        led.toggle().unwrap();
+        assert_eq!(sim::register(PORTB), 0);
+    }
+
+    #[test]
+    fn input_modes_control_the_pull_up() {
+        let _guard = setup();
+
+        let portl = PL::split().unwrap();
+        let button = portl.p2.into_output_high().into_pull_up_input();
+        assert_eq!(sim::register(DDRL), 0);
+        assert_eq!(sim::register(PORTL), 1 << 2);
+
+        let _sensor = button.into_floating_input();
+        assert_eq!(sim::register(PORTL), 0);
+    }
+
+    #[test]
+    fn input_pin_reads_pinx() {
+        let _guard = setup();
+
+        let mut button = PB::split().unwrap().p0.into_pull_up_input();
+        sim::set_register(PINB, 0b1111_1110);
+        assert!(button.is_low().unwrap());
+        sim::set_register(PINB, 0b0000_0001);
+        assert!(button.is_high().unwrap());
+    }
+
+    /// 포트의 핀 8개를 모두 High 출력으로 바꾸고 그 포트의 DDRx/PORTx를 확인
+    fn drive_all<P: PortId>(parts: Parts<P>) {
+        let pins = [
+            parts.p0.into_output_high().pin_number(),
+            parts.p1.into_output_high().pin_number(),
+            parts.p2.into_output_high().pin_number(),
+            parts.p3.into_output_high().pin_number(),
+            parts.p4.into_output_high().pin_number(),
+            parts.p5.into_output_high().pin_number(),
+            parts.p6.into_output_high().pin_number(),
+            parts.p7.into_output_high().pin_number(),
+        ];
+        assert_eq!(pins, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(sim::register(P::PORT.ddr_addr()), 0xFF);
+        assert_eq!(sim::register(P::PORT.port_addr()), 0xFF);
+    }
+
+    #[test]
+    fn every_port_uses_its_own_registers() {
+        let _guard = setup();
+
+        drive_all(PA::split().unwrap());
+        drive_all(PB::split().unwrap());
+        drive_all(PC::split().unwrap());
+        drive_all(PD::split().unwrap());
+        drive_all(PE::split().unwrap());
+        drive_all(PF::split().unwrap());
+        drive_all(PG::split().unwrap());
+        drive_all(PH::split().unwrap());
+        drive_all(PJ::split().unwrap());
+        drive_all(PK::split().unwrap());
+        drive_all(PL::split().unwrap());
+    }
+
+    /// embedded-hal 트레이트만 아는 드라이버 흉내
+    fn follow<I: InputPin, O: StatefulOutputPin>(input: &mut I, output: &mut O) {
+        if input.is_high().unwrap() != output.is_set_high().unwrap() {
+            output.toggle().unwrap();
+        }
+    }
+
+    #[test]
+    fn pins_work_through_embedded_hal_traits() {
+        let _guard = setup();
+
+        let portb = PB::split().unwrap();
+        let mut button = portb.p0.into_floating_input();
+        let mut led = PL::split().unwrap().p1.into_output();
+
+        sim::set_register(PINB, 1);
+        follow(&mut button, &mut led);
+        assert_eq!(sim::register(PORTL), 1 << 1);
+        sim::set_register(PINB, 0);
+        follow(&mut button, &mut led);
+        assert_eq!(sim::register(PORTL), 0);
+
+        led.set_high().unwrap();
+        assert!(led.is_set_high().unwrap());
+    }
+}
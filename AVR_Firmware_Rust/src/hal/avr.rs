@@ -0,0 +1,294 @@
+//
+// AVR 백엔드: avr-device PAC으로 실제 레지스터에 접근
+//
+use core::ptr::{read_volatile, write_volatile};
+
+pub use avr_device::atmega2560 as pac;
+pub use avr_device::interrupt;
+
+use crate::scheduler::SleepMode;
+use crate::tick::{TickConfig, TickTimer};
+
+//
+// tick 타이머
+//
+// OCFnA / OCIEnA 비트 위치 (세 타이머 공통)
+const OCF_A: u8 = 1;
+const OCIE_A: u8 = 1;
+
+impl TickTimer {
+    /// CTC 모드 + 비교 매치 A 인터럽트로 설정
+    pub fn configure(&self, config: &TickConfig) {
+        match self {
+            TickTimer::Timer0(tc0) => {
+                // TCCR0A: CTC 모드 설정 (WGM0 = 2 → WGM01=1, WGM00=0)
+                tc0.tccr0a.write(|w| w.wgm0().bits(2));
+                // TCCR0B: 분주비 설정 (CS0)
+                tc0.tccr0b.write(|w| w.cs0().bits(config.cs_bits));
+                // OCR0A: 비교값
+                tc0.ocr0a.write(|w| unsafe { w.bits(config.compare as u8) });
+                // TIMSK0: 출력 비교 A 매치 인터럽트 활성화 (OCIE0A=1)
+                tc0.timsk0.write(|w| w.ocie0a().set_bit());
+            }
+            TickTimer::Timer1(tc1) => {
+                // CTC 모드 4: WGM13..10 = 0100 (TCCR1B.WGM12 = bit 3)
+                tc1.tccr1a.write(|w| unsafe { w.bits(0) });
+                tc1.tccr1b.write(|w| unsafe { w.bits((1 << 3) | config.cs_bits) });
+                tc1.ocr1a.write(|w| unsafe { w.bits(config.compare) });
+                tc1.timsk1.write(|w| unsafe { w.bits(1 << OCIE_A) });
+            }
+            TickTimer::Timer2(tc2) => {
+                // CTC 모드: WGM22..20 = 010 (TCCR2A.WGM21 = bit 1)
+                tc2.tccr2a.write(|w| unsafe { w.bits(1 << 1) });
+                tc2.tccr2b.write(|w| unsafe { w.bits(config.cs_bits) });
+                tc2.ocr2a.write(|w| unsafe { w.bits(config.compare as u8) });
+                tc2.timsk2.write(|w| unsafe { w.bits(1 << OCIE_A) });
+            }
+        }
+    }
+
+    /// 현재 카운터 값
+    pub fn counter(&self) -> u32 {
+        match self {
+            TickTimer::Timer0(tc0) => tc0.tcnt0.read().bits() as u32,
+            TickTimer::Timer1(tc1) => tc1.tcnt1.read().bits() as u32,
+            TickTimer::Timer2(tc2) => tc2.tcnt2.read().bits() as u32,
+        }
+    }
+
+    /// tickless idle: 분주비를 `idle_prescaler`로 바꾸고 `counts` 카운트 뒤에 비교 매치
+    pub fn start_idle(&self, config: &TickConfig, counts: u32) {
+        self.reload(config.idle_cs_bits, (counts - 1) as u16, 0);
+    }
+
+    /// tickless idle 종료: 원래 tick 설정으로 돌아가 카운터를 `counts`부터 다시 셈
+    ///
+    /// 대기 중인 비교 매치 플래그는 지웁니다. (늘린 구간의 비교 매치는 호출한 쪽이 계산에 반영)
+    pub fn resume(&self, config: &TickConfig, counts: u32) {
+        self.reload(config.cs_bits, config.compare, counts as u16);
+    }
+
+    /// 타이머를 멈추고 비교값/카운터를 바꾼 뒤 `cs_bits` 분주로 다시 시작
+    fn reload(&self, cs_bits: u8, compare: u16, count: u16) {
+        match self {
+            TickTimer::Timer0(tc0) => {
+                tc0.tccr0b.write(|w| w.cs0().bits(0));
+                tc0.ocr0a.write(|w| unsafe { w.bits(compare as u8) });
+                tc0.tcnt0.write(|w| unsafe { w.bits(count as u8) });
+                tc0.tifr0.write(|w| unsafe { w.bits(1 << OCF_A) });
+                tc0.tccr0b.write(|w| w.cs0().bits(cs_bits));
+            }
+            TickTimer::Timer1(tc1) => {
+                tc1.tccr1b.write(|w| unsafe { w.bits(1 << 3) });
+                tc1.ocr1a.write(|w| unsafe { w.bits(compare) });
+                tc1.tcnt1.write(|w| unsafe { w.bits(count) });
+                tc1.tifr1.write(|w| unsafe { w.bits(1 << OCF_A) });
+                tc1.tccr1b.write(|w| unsafe { w.bits((1 << 3) | cs_bits) });
+            }
+            TickTimer::Timer2(tc2) => {
+                tc2.tccr2b.write(|w| unsafe { w.bits(0) });
+                tc2.ocr2a.write(|w| unsafe { w.bits(compare as u8) });
+                tc2.tcnt2.write(|w| unsafe { w.bits(count as u8) });
+                tc2.tifr2.write(|w| unsafe { w.bits(1 << OCF_A) });
+                tc2.tccr2b.write(|w| unsafe { w.bits(cs_bits) });
+            }
+        }
+    }
+
+    /// 비교 매치가 발생했지만 아직 tick 인터럽트가 처리되지 않았는지 (OCFnA)
+    pub fn compare_pending(&self) -> bool {
+        let flags = match self {
+            TickTimer::Timer0(tc0) => tc0.tifr0.read().bits(),
+            TickTimer::Timer1(tc1) => tc1.tifr1.read().bits(),
+            TickTimer::Timer2(tc2) => tc2.tifr2.read().bits(),
+        };
+        flags & (1 << OCF_A) != 0
+    }
+}
+
+//
+// 슬립
+//
+/// 인터럽트를 켜면서 슬립에 들어가고, 깨어나면 SE를 해제합니다.
+///
+/// 인터럽트를 막은 상태에서 호출해야 합니다. (`sei` 다음 명령은 항상 실행되므로
+/// 검사와 슬립 사이에 인터럽트가 끼어들어 깨어날 기회를 놓치지 않음)
+pub fn sleep(mode: SleepMode) {
+    let cpu = unsafe { &*pac::CPU::ptr() };
+    cpu.smcr.write(|w| match mode {
+        SleepMode::Idle => w.sm().idle().se().set_bit(),
+    });
+    unsafe { core::arch::asm!("sei", "sleep") };
+    cpu.smcr.write(|w| w.se().clear_bit());
+}
+
+//
+// USART0
+//
+/// 비동기 8N1, 2배속(U2X0) 모드로 초기화
+pub fn usart_init(usart0: &pac::USART0, ubrr: u16) {
+    // 1) Double Speed 모드 활성화 (U2X0=1)
+    usart0.ucsr0a.modify(|_, w| w.u2x0().set_bit());
+
+    // 2) UBRR0 설정
+    usart0.ubrr0.write(|w| unsafe { w.bits(ubrr) });
+
+    // 3) UCSR0C: 비동기, 패리티 없음, 1 스톱비트, 데이터 8비트
+    usart0.ucsr0c.write(|w| {
+        w.umsel0().usart_async() // 비동기
+            .upm0().disabled()      // 패리티 없음
+            .usbs0().bit(false)     // 1 스톱비트
+            .ucsz0().chr8()         // 8비트
+    });
+
+    // 4) UCSR0B: RX/TX Enable
+    usart0.ucsr0b.write(|w| {
+        w.rxen0().set_bit()
+            .txen0().set_bit()
+            .udrie0().clear_bit() // 일단 비활성, 필요 시 인터럽트 enable
+    });
+}
+
+/// UDRE0(송신 버퍼 빔) 인터럽트 활성화/비활성화
+pub fn usart_set_udre_interrupt(usart0: &pac::USART0, enable: bool) {
+    usart0.ucsr0b.modify(|_, w| w.udrie0().bit(enable));
+}
+
+/// 수신 데이터가 있으면 UDR0에서 1바이트 읽기
+pub fn usart_read(usart0: &pac::USART0) -> Option<u8> {
+    if usart0.ucsr0a.read().rxc0().bit_is_set() {
+        Some(usart0.udr0.read().bits())
+    } else {
+        None
+    }
+}
+
+/// UDR0에 1바이트 쓰기 (UDRE0 인터럽트 안에서 호출)
+pub fn usart_write(usart0: &pac::USART0, data: u8) {
+    usart0.udr0.write(|w| unsafe { w.bits(data) });
+}
+
+//
+// Watchdog (데이터시트 12.5)
+//
+const WDCE: u8 = 4;
+const WDE: u8 = 3;
+const WDRF: u8 = 3;
+
+/// 직전 리셋이 WDT에 의한 것인지 읽고 WDRF를 클리어 (WDRF가 set이면 WDE를 끌 수 없음)
+pub fn watchdog_take_reset_flag() -> bool {
+    let cpu = unsafe { &*pac::CPU::ptr() };
+    let mcusr = cpu.mcusr.read().bits();
+    cpu.mcusr.write(|w| unsafe { w.bits(mcusr & !(1 << WDRF)) });
+    mcusr & (1 << WDRF) != 0
+}
+
+/// 타임 시퀀스로 WDTCSR 설정: WDCE|WDE 후 4사이클 안에 새 설정 기록
+///
+/// 인터럽트를 막은 상태에서 호출해야 합니다.
+pub fn watchdog_configure(wdt: &pac::WDT, wdtcsr: u8) {
+    avr_device::asm::wdr();
+    wdt.wdtcsr.write(|w| unsafe { w.bits((1 << WDCE) | (1 << WDE)) });
+    wdt.wdtcsr.write(|w| unsafe { w.bits(wdtcsr) });
+}
+
+/// WDT 카운터 리셋
+#[inline(always)]
+pub fn wdr() {
+    avr_device::asm::wdr();
+}
+
+//
+// 데이터 공간 레지스터 (포트 등 주소로 직접 접근하는 레지스터)
+//
+/// # Safety
+/// `addr`은 유효한 I/O 레지스터 주소여야 합니다.
+#[inline(always)]
+pub unsafe fn read_reg(addr: *mut u8) -> u8 {
+    unsafe { read_volatile(addr) }
+}
+
+/// # Safety
+/// `addr`은 유효한 I/O 레지스터 주소여야 합니다.
+#[inline(always)]
+pub unsafe fn write_reg(addr: *mut u8, value: u8) {
+    unsafe { write_volatile(addr, value) }
+}
+
+/// 레지스터의 한 비트를 1로 만듭니다. 인터럽트가 끼어들어도 다른 비트를 망가뜨리지 않습니다.
+///
+/// 주소/비트가 실행 중에 정해지는 경우용으로, 임계구역 안에서 read-modify-write 합니다.
+/// 호출하는 곳마다 인라인되지 않도록 함수 하나로 둡니다.
+/// (주소/비트가 상수면 `set_const_bit()`이 SBI 한 명령으로 처리)
+///
+/// # Safety
+/// `addr`은 유효한 I/O 레지스터 주소여야 합니다.
+#[inline(never)]
+pub unsafe fn set_reg_bit(addr: *mut u8, bit: u8) {
+    interrupt::free(|_| unsafe { write_volatile(addr, read_volatile(addr) | (1 << bit)) });
+}
+
+/// 레지스터의 한 비트를 0으로 만듭니다. (`set_reg_bit`의 clear 버전)
+///
+/// # Safety
+/// `addr`은 유효한 I/O 레지스터 주소여야 합니다.
+#[inline(never)]
+pub unsafe fn clear_reg_bit(addr: *mut u8, bit: u8) {
+    interrupt::free(|_| unsafe { write_volatile(addr, read_volatile(addr) & !(1 << bit)) });
+}
+
+/// 주소가 컴파일 타임 상수인 레지스터 (`set_const_bit()` / `clear_const_bit()`용)
+pub trait ConstRegister {
+    /// 데이터 공간 주소
+    const ADDR: usize;
+}
+
+// SBI/CBI는 I/O 주소 0x00~0x1F(데이터 주소 0x20~0x3F)에만 쓸 수 있고, 주소와 비트가
+// 명령어에 들어가는 즉시값이므로 둘 다 상수일 때만 사용합니다.
+// 낮은 I/O 영역이 아니면 분기가 컴파일 타임에 접혀 `set_reg_bit()` 호출만 남습니다.
+// (쓰이지 않는 분기의 asm도 어셈블러가 범위를 검사하므로 즉시값은 마스크해서 넣음)
+
+/// `R`의 `BIT` 비트를 1로 만듭니다. 낮은 I/O 영역이면 SBI 한 명령입니다.
+///
+/// # Safety
+/// `R::ADDR`은 유효한 I/O 레지스터 주소여야 합니다.
+#[inline(always)]
+pub unsafe fn set_const_bit<R: ConstRegister, const BIT: u8>() {
+    if is_low_io(R::ADDR) {
+        unsafe {
+            core::arch::asm!(
+                "sbi {io}, {bit}",
+                io = const R::ADDR.wrapping_sub(0x20) & 0x1F,
+                bit = const BIT & 0x07,
+                options(nostack, preserves_flags)
+            );
+        }
+    } else {
+        unsafe { set_reg_bit(R::ADDR as *mut u8, BIT) }
+    }
+}
+
+/// `R`의 `BIT` 비트를 0으로 만듭니다. 낮은 I/O 영역이면 CBI 한 명령입니다.
+///
+/// # Safety
+/// `R::ADDR`은 유효한 I/O 레지스터 주소여야 합니다.
+#[inline(always)]
+pub unsafe fn clear_const_bit<R: ConstRegister, const BIT: u8>() {
+    if is_low_io(R::ADDR) {
+        unsafe {
+            core::arch::asm!(
+                "cbi {io}, {bit}",
+                io = const R::ADDR.wrapping_sub(0x20) & 0x1F,
+                bit = const BIT & 0x07,
+                options(nostack, preserves_flags)
+            );
+        }
+    } else {
+        unsafe { clear_reg_bit(R::ADDR as *mut u8, BIT) }
+    }
+}
+
+/// SBI/CBI로 접근할 수 있는 데이터 공간 주소(0x20~0x3F)인지
+const fn is_low_io(addr: usize) -> bool {
+    addr >= 0x20 && addr < 0x40
+}
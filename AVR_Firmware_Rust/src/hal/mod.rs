@@ -0,0 +1,27 @@
+//
+// 하드웨어 추상화 계층 (HAL)
+//
+// 레지스터/인터럽트/슬립에 접근하는 코드는 모두 이 모듈을 거칩니다.
+// 타깃에 따라 백엔드가 선택되며, 두 백엔드는 같은 이름의 API를 제공합니다.
+//
+//   target_arch = "avr"  → avr.rs : avr-device PAC으로 실제 레지스터에 접근
+//   그 외 (x86 호스트)    → sim.rs : 가상 레지스터 파일 + 가상 tick (`cargo test`용)
+//
+// 제공하는 API
+//   - `interrupt`  : free / Mutex / CriticalSection / enable / disable
+//   - `pac`        : 주변장치 소유권 토큰 (`pac::Peripherals::take()`, TC0, USART0, WDT ...)
+//   - `TickTimer`의 configure / counter / compare_pending
+//   - `sleep()`, `usart_*()`, `watchdog_*()`, `wdr()`, `read_reg()` / `write_reg()`
+//   - `set_reg_bit()` / `clear_reg_bit()` (실행 중 주소), `set_const_bit()` / `clear_const_bit()` (상수 주소 → SBI/CBI)
+//
+// 호스트에서 테스트 실행: `cargo test-host` (.cargo/config.toml의 alias)
+//
+#[cfg(target_arch = "avr")]
+mod avr;
+#[cfg(target_arch = "avr")]
+pub use avr::*;
+
+#[cfg(not(target_arch = "avr"))]
+pub mod sim;
+#[cfg(not(target_arch = "avr"))]
+pub use sim::*;
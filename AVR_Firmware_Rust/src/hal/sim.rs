@@ -0,0 +1,478 @@
+
+//
+// 호스트 시뮬레이션 백엔드 (x86에서 `cargo test`용)
+//
+// - 인터럽트: 실제 인터럽트는 없고, 아래 시뮬레이션 API가 ISR 본문을 직접 호출합니다.
+//   `interrupt::free`는 전역 인터럽트 플래그만 흉내 냅니다.
+// - 시간: 가상 타이머. `advance_us()`가 `TICK_US`마다 스케줄러 tick을 발생시키고,
+//   `scheduler::idle()`의 슬립은 다음 tick까지 가상 시간을 진행합니다.
+// - 레지스터: 데이터 공간 0x000 ~ 0x1FF를 흉내 낸 가상 레지스터 파일 (포트 PINx/DDRx/PORTx)
+// - USART0: 송신 바이트는 캡처 버퍼에 쌓이고, 수신 바이트는 `serial_inject()`로 넣습니다.
+//   UDRE 인터럽트는 보오율에 맞춰 가상 시간이 흐를 때 발생합니다.
+//
+// 전역 상태를 공유하므로 테스트는 `lock()`으로 직렬화한 뒤 `reset()`으로 초기화합니다.
+//
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, Ordering};
+
+use crate::scheduler::SleepMode;
+use crate::tick::{TickConfig, TickTimer, CPU_FREQUENCY, TICK_US};
+
+//
+// 인터럽트 / 임계구역
+//
+pub mod interrupt {
+    use core::marker::PhantomData;
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    static ENABLED: AtomicBool = AtomicBool::new(false);
+
+    /// 임계구역 안에 있다는 증표
+    #[derive(Clone, Copy)]
+    pub struct CriticalSection<'cs>(PhantomData<&'cs ()>);
+
+    /// 임계구역에서만 내부 값에 접근할 수 있는 컨테이너
+    pub struct Mutex<T>(T);
+
+    impl<T> Mutex<T> {
+        pub const fn new(value: T) -> Self {
+            Self(value)
+        }
+
+        pub fn borrow<'cs>(&'cs self, _cs: CriticalSection<'cs>) -> &'cs T {
+            &self.0
+        }
+    }
+
+    // 테스트는 `sim::lock()`으로 직렬화되므로 동시에 접근하지 않습니다.
+    unsafe impl<T: Send> Sync for Mutex<T> {}
+
+    /// 전역 인터럽트 비활성화. 이전에 활성화되어 있었는지 반환합니다.
+    pub fn disable() -> bool {
+        ENABLED.swap(false, Ordering::SeqCst)
+    }
+
+    /// 전역 인터럽트 활성화
+    ///
+    /// # Safety
+    /// 실제 하드웨어와 시그니처를 맞추기 위한 것으로, 시뮬레이션에서는 항상 안전합니다.
+    pub unsafe fn enable() {
+        ENABLED.store(true, Ordering::SeqCst);
+    }
+
+    pub fn free<F, R>(f: F) -> R
+    where
+        F: FnOnce(CriticalSection) -> R,
+    {
+        let was_enabled = disable();
+        let result = f(CriticalSection(PhantomData));
+        if was_enabled {
+            unsafe { enable() };
+        }
+        result
+    }
+}
+
+use interrupt::Mutex;
+
+//
+// 주변장치 소유권 토큰 (레지스터는 없고 타입만 PAC과 맞춤)
+//
+// 주변장치 이름은 avr-device(`pac::WDT` 등)와 같게 맞춤
+#[allow(non_snake_case, clippy::upper_case_acronyms, dead_code)]
+pub mod pac {
+    pub struct TC0;
+    pub struct TC1;
+    pub struct TC2;
+    pub struct USART0;
+    pub struct WDT;
+
+    // 쓰지 않는 주변장치도 avr-device와 같게 둠
+    pub struct Peripherals {
+        pub TC0: TC0,
+        pub TC1: TC1,
+        pub TC2: TC2,
+        pub USART0: USART0,
+        pub WDT: WDT,
+    }
+
+    impl Peripherals {
+        /// 시뮬레이션에서는 테스트마다 새로 받을 수 있도록 항상 Some
+        pub fn take() -> Option<Self> {
+            Some(Self {
+                TC0,
+                TC1,
+                TC2,
+                USART0,
+                WDT,
+            })
+        }
+    }
+}
+
+//
+// 가상 타이머
+//
+/// `timer_init()`으로 설정된 tick 설정 (None이면 타이머 정지)
+static TICK_CONFIG: Mutex<RefCell<Option<TickConfig>>> = Mutex::new(RefCell::new(None));
+/// 마지막 tick 이후 흐른 가상 시간 (us, 0 ~ 비교 주기-1)
+static SINCE_TICK_US: AtomicU32 = AtomicU32::new(0);
+/// tickless idle로 늘린 비교 주기 (us, 0이면 평소 주기 `TICK_US`)
+static IDLE_INTERVAL_US: AtomicU32 = AtomicU32::new(0);
+/// `sleep()` 호출 횟수
+static SLEEP_COUNT: AtomicU32 = AtomicU32::new(0);
+
+impl TickTimer {
+    pub fn configure(&self, config: &TickConfig) {
+        interrupt::free(|cs| *TICK_CONFIG.borrow(cs).borrow_mut() = Some(*config));
+        SINCE_TICK_US.store(0, Ordering::SeqCst);
+        IDLE_INTERVAL_US.store(0, Ordering::SeqCst);
+    }
+
+    pub fn counter(&self) -> u32 {
+        let config = interrupt::free(|cs| *TICK_CONFIG.borrow(cs).borrow());
+        let prescaler = match config {
+            Some(config) if IDLE_INTERVAL_US.load(Ordering::SeqCst) > 0 => config.idle_prescaler,
+            Some(config) => config.prescaler,
+            None => 1,
+        };
+        SINCE_TICK_US.load(Ordering::SeqCst) * (CPU_FREQUENCY / 1_000_000) / prescaler
+    }
+
+    pub fn start_idle(&self, config: &TickConfig, counts: u32) {
+        IDLE_INTERVAL_US.store(counts * config.idle_count_us(), Ordering::SeqCst);
+        SINCE_TICK_US.store(0, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self, config: &TickConfig, counts: u32) {
+        IDLE_INTERVAL_US.store(0, Ordering::SeqCst);
+        SINCE_TICK_US.store(config.counts_to_us(counts), Ordering::SeqCst);
+    }
+
+    /// tick은 `advance_us()`가 즉시 처리하므로 대기 중인 비교 매치는 없음
+    pub fn compare_pending(&self) -> bool {
+        false
+    }
+}
+
+/// 가상 시간을 `us`만큼 진행합니다. 그 사이 tick/UDRE 인터럽트를 순서대로 발생시킵니다.
+pub fn advance_us(us: u32) {
+    let running = interrupt::free(|cs| TICK_CONFIG.borrow(cs).borrow().is_some());
+    let mut remaining = us;
+    while remaining > 0 {
+        let since_tick = SINCE_TICK_US.load(Ordering::SeqCst);
+        let period = tick_period_us();
+        let step = remaining.min(period - since_tick);
+        remaining -= step;
+        usart_elapse(step);
+
+        if !running {
+            continue;
+        }
+        if since_tick + step == period {
+            SINCE_TICK_US.store(0, Ordering::SeqCst);
+            interrupt::free(crate::scheduler::tick);
+        } else {
+            SINCE_TICK_US.store(since_tick + step, Ordering::SeqCst);
+        }
+    }
+}
+
+/// 지금 비교 매치 주기 (tickless idle 중이면 늘린 주기)
+fn tick_period_us() -> u32 {
+    match IDLE_INTERVAL_US.load(Ordering::SeqCst) {
+        0 => TICK_US,
+        interval => interval,
+    }
+}
+
+/// 슬립: 인터럽트를 켜고 다음 비교 매치(tick 또는 tickless idle로 늘린 주기)까지 가상 시간을 진행
+pub fn sleep(_mode: SleepMode) {
+    let running = interrupt::free(|cs| TICK_CONFIG.borrow(cs).borrow().is_some());
+    assert!(running, "sim: sleep() without a running tick timer would never wake up");
+
+    SLEEP_COUNT.fetch_add(1, Ordering::SeqCst);
+    unsafe { interrupt::enable() };
+    advance_us(tick_period_us() - SINCE_TICK_US.load(Ordering::SeqCst));
+}
+
+/// `sleep()`이 호출된 횟수
+#[cfg(test)]
+pub fn sleep_count() -> u32 {
+    SLEEP_COUNT.load(Ordering::SeqCst)
+}
+
+//
+// 가상 USART0
+//
+const SERIAL_CAPTURE_SIZE: usize = 1024;
+const SERIAL_RX_SIZE: usize = 64;
+
+struct SimUsart {
+    /// UBRR 설정값으로 계산한 1바이트(10비트) 전송 시간 (None이면 초기화 전)
+    byte_us: Option<u32>,
+    udre_interrupt: bool,
+    /// 다음 바이트 전송까지 누적된 시간
+    elapsed_us: u32,
+    tx: [u8; SERIAL_CAPTURE_SIZE],
+    tx_len: usize,
+    rx: [u8; SERIAL_RX_SIZE],
+    rx_head: usize,
+    rx_len: usize,
+}
+
+impl SimUsart {
+    const fn new() -> Self {
+        Self {
+            byte_us: None,
+            udre_interrupt: false,
+            elapsed_us: 0,
+            tx: [0; SERIAL_CAPTURE_SIZE],
+            tx_len: 0,
+            rx: [0; SERIAL_RX_SIZE],
+            rx_head: 0,
+            rx_len: 0,
+        }
+    }
+}
+
+static USART: Mutex<RefCell<SimUsart>> = Mutex::new(RefCell::new(SimUsart::new()));
+
+pub fn usart_init(_usart0: &pac::USART0, ubrr: u16) {
+    // 2배속 모드: baud = F_CPU / (8 * (UBRR + 1))
+    let baud = CPU_FREQUENCY / (8 * (ubrr as u32 + 1));
+    interrupt::free(|cs| {
+        let mut usart = USART.borrow(cs).borrow_mut();
+        usart.byte_us = Some((10 * 1_000_000 / baud).max(1));
+        usart.udre_interrupt = false;
+        usart.elapsed_us = 0;
+    });
+}
+
+pub fn usart_set_udre_interrupt(_usart0: &pac::USART0, enable: bool) {
+    interrupt::free(|cs| USART.borrow(cs).borrow_mut().udre_interrupt = enable);
+}
+
+pub fn usart_read(_usart0: &pac::USART0) -> Option<u8> {
+    interrupt::free(|cs| {
+        let mut usart = USART.borrow(cs).borrow_mut();
+        if usart.rx_len == 0 {
+            return None;
+        }
+        let byte = usart.rx[usart.rx_head];
+        usart.rx_head = (usart.rx_head + 1) % SERIAL_RX_SIZE;
+        usart.rx_len -= 1;
+        Some(byte)
+    })
+}
+
+pub fn usart_write(_usart0: &pac::USART0, data: u8) {
+    interrupt::free(|cs| {
+        let mut usart = USART.borrow(cs).borrow_mut();
+        let len = usart.tx_len;
+        assert!(len < SERIAL_CAPTURE_SIZE, "sim: serial capture buffer full");
+        usart.tx[len] = data;
+        usart.tx_len += 1;
+    });
+}
+
+/// 가상 시간이 흐르는 동안 UDRE 인터럽트가 켜져 있으면 바이트마다 ISR 호출
+fn usart_elapse(us: u32) {
+    let mut budget = us;
+    loop {
+        let fire = interrupt::free(|cs| {
+            let mut usart = USART.borrow(cs).borrow_mut();
+            let Some(byte_us) = usart.byte_us.filter(|_| usart.udre_interrupt) else {
+                usart.elapsed_us = 0;
+                return false;
+            };
+            let needed = byte_us - usart.elapsed_us;
+            if budget < needed {
+                usart.elapsed_us += budget;
+                return false;
+            }
+            budget -= needed;
+            usart.elapsed_us = 0;
+            true
+        });
+        if !fire {
+            break;
+        }
+        interrupt::free(crate::serial::udre_isr);
+    }
+}
+
+/// 송신 버퍼가 빌 때까지(UDRE 인터럽트가 꺼질 때까지) ISR을 실행합니다. (가상 시간은 그대로)
+#[cfg(test)]
+pub fn serial_flush() {
+    while interrupt::free(|cs| USART.borrow(cs).borrow().udre_interrupt) {
+        interrupt::free(crate::serial::udre_isr);
+    }
+}
+
+/// 지금까지 송신된 바이트를 `buf`에 복사하고 캡처 버퍼를 비웁니다. 복사한 길이를 반환합니다.
+#[cfg(test)]
+pub fn serial_take_output(buf: &mut [u8]) -> usize {
+    interrupt::free(|cs| {
+        let mut usart = USART.borrow(cs).borrow_mut();
+        let len = usart.tx_len.min(buf.len());
+        buf[..len].copy_from_slice(&usart.tx[..len]);
+        usart.tx_len = 0;
+        len
+    })
+}
+
+/// 수신 데이터 주입 (RX 버퍼가 가득 차면 false)
+#[cfg(test)]
+pub fn serial_inject(bytes: &[u8]) -> bool {
+    interrupt::free(|cs| {
+        let mut usart = USART.borrow(cs).borrow_mut();
+        for &b in bytes {
+            if usart.rx_len == SERIAL_RX_SIZE {
+                return false;
+            }
+            let tail = (usart.rx_head + usart.rx_len) % SERIAL_RX_SIZE;
+            usart.rx[tail] = b;
+            usart.rx_len += 1;
+        }
+        true
+    })
+}
+
+//
+// 가상 Watchdog
+//
+static WATCHDOG_RESET_FLAG: AtomicBool = AtomicBool::new(false);
+static WATCHDOG_CONFIG: AtomicU8 = AtomicU8::new(0);
+static WDR_COUNT: AtomicU32 = AtomicU32::new(0);
+
+pub fn watchdog_take_reset_flag() -> bool {
+    WATCHDOG_RESET_FLAG.swap(false, Ordering::SeqCst)
+}
+
+pub fn watchdog_configure(_wdt: &pac::WDT, wdtcsr: u8) {
+    WDR_COUNT.fetch_add(1, Ordering::SeqCst);
+    WATCHDOG_CONFIG.store(wdtcsr, Ordering::SeqCst);
+}
+
+pub fn wdr() {
+    WDR_COUNT.fetch_add(1, Ordering::SeqCst);
+}
+
+/// 다음 `watchdog::init()`이 WDT 리셋 후 부팅으로 보도록 MCUSR.WDRF를 세팅
+#[cfg(test)]
+pub fn set_watchdog_reset_flag() {
+    WATCHDOG_RESET_FLAG.store(true, Ordering::SeqCst);
+}
+
+/// `wdr()` 실행 횟수
+#[cfg(test)]
+pub fn wdr_count() -> u32 {
+    WDR_COUNT.load(Ordering::SeqCst)
+}
+
+/// 마지막으로 설정한 WDTCSR 값
+#[cfg(test)]
+pub fn watchdog_config() -> u8 {
+    WATCHDOG_CONFIG.load(Ordering::SeqCst)
+}
+
+//
+// 가상 레지스터 파일 (데이터 공간 0x000 ~ 0x1FF)
+//
+const REGISTER_SPACE: usize = 0x200;
+static REGISTERS: [AtomicU8; REGISTER_SPACE] = [const { AtomicU8::new(0) }; REGISTER_SPACE];
+
+/// # Safety
+/// 실제 하드웨어와 시그니처를 맞추기 위한 것으로, 시뮬레이션에서는 항상 안전합니다.
+pub unsafe fn read_reg(addr: *mut u8) -> u8 {
+    register(addr as usize)
+}
+
+/// # Safety
+/// 실제 하드웨어와 시그니처를 맞추기 위한 것으로, 시뮬레이션에서는 항상 안전합니다.
+pub unsafe fn write_reg(addr: *mut u8, value: u8) {
+    let addr = addr as usize;
+    if is_pin_register(addr) {
+        // 실제 칩처럼 PINx에 1을 쓰면 PORTx의 해당 비트가 토글됨
+        REGISTERS[addr + 2].fetch_xor(value, Ordering::SeqCst);
+    } else {
+        set_register(addr, value);
+    }
+}
+
+/// # Safety
+/// 실제 하드웨어와 시그니처를 맞추기 위한 것으로, 시뮬레이션에서는 항상 안전합니다.
+pub unsafe fn set_reg_bit(addr: *mut u8, bit: u8) {
+    REGISTERS[addr as usize].fetch_or(1 << bit, Ordering::SeqCst);
+}
+
+/// # Safety
+/// 실제 하드웨어와 시그니처를 맞추기 위한 것으로, 시뮬레이션에서는 항상 안전합니다.
+pub unsafe fn clear_reg_bit(addr: *mut u8, bit: u8) {
+    REGISTERS[addr as usize].fetch_and(!(1 << bit), Ordering::SeqCst);
+}
+
+/// 주소가 컴파일 타임 상수인 레지스터 (`set_const_bit()` / `clear_const_bit()`용)
+pub trait ConstRegister {
+    /// 데이터 공간 주소
+    const ADDR: usize;
+}
+
+/// # Safety
+/// 실제 하드웨어와 시그니처를 맞추기 위한 것으로, 시뮬레이션에서는 항상 안전합니다.
+pub unsafe fn set_const_bit<R: ConstRegister, const BIT: u8>() {
+    unsafe { set_reg_bit(R::ADDR as *mut u8, BIT) }
+}
+
+/// # Safety
+/// 실제 하드웨어와 시그니처를 맞추기 위한 것으로, 시뮬레이션에서는 항상 안전합니다.
+pub unsafe fn clear_const_bit<R: ConstRegister, const BIT: u8>() {
+    unsafe { clear_reg_bit(R::ADDR as *mut u8, BIT) }
+}
+
+/// PINA~PING(0x20~0x32), PINH~PINL(0x100~0x109) 주소인지
+fn is_pin_register(addr: usize) -> bool {
+    matches!(addr, 0x20..=0x32 if (addr - 0x20).is_multiple_of(3))
+        || matches!(addr, 0x100..=0x109 if (addr - 0x100).is_multiple_of(3))
+}
+
+/// 가상 레지스터 값 읽기 (테스트에서 DDRx/PORTx 확인용)
+pub fn register(addr: usize) -> u8 {
+    REGISTERS[addr].load(Ordering::SeqCst)
+}
+
+/// 가상 레지스터 값 쓰기 (테스트에서 PINx 입력 흉내용, PINx도 그대로 저장)
+pub fn set_register(addr: usize, value: u8) {
+    REGISTERS[addr].store(value, Ordering::SeqCst);
+}
+
+//
+// 테스트 지원
+//
+/// 전역 상태를 쓰는 테스트를 직렬화하는 잠금
+#[cfg(test)]
+pub fn lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// 시뮬레이션 하드웨어 상태를 전원 투입 직후로 되돌립니다.
+/// (스케줄러 등 각 모듈의 전역 상태는 해당 모듈의 테스트가 초기화)
+#[cfg(test)]
+pub fn reset() {
+    interrupt::disable();
+    interrupt::free(|cs| {
+        *TICK_CONFIG.borrow(cs).borrow_mut() = None;
+        *USART.borrow(cs).borrow_mut() = SimUsart::new();
+    });
+    SINCE_TICK_US.store(0, Ordering::SeqCst);
+    IDLE_INTERVAL_US.store(0, Ordering::SeqCst);
+    SLEEP_COUNT.store(0, Ordering::SeqCst);
+    WATCHDOG_RESET_FLAG.store(false, Ordering::SeqCst);
+    WATCHDOG_CONFIG.store(0, Ordering::SeqCst);
+    WDR_COUNT.store(0, Ordering::SeqCst);
+    for register in REGISTERS.iter() {
+        register.store(0, Ordering::SeqCst);
+    }
+}
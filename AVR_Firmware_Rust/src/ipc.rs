@@ -0,0 +1,287 @@
+
+//
+// 태스크/ISR 간 통신: 메시지 큐, 이벤트 플래그
+//
+// - 모두 정적으로 할당하며(`static`), 내부는 `interrupt::free` 임계구역으로 보호하므로
+//   태스크와 ISR 어디서든 호출할 수 있습니다.
+// - 수신 태스크를 지정해 두면 데이터/플래그가 들어올 때 `TaskHandle::notify()`로
+//   해당 스케줄러 태스크를 즉시 ready로 만듭니다. (폴링 불필요)
+//
+// 예) UART RX ISR → RX_QUEUE.send(byte) → 파서 태스크(task_add_event)가 바로 실행
+//
+// 큐/플래그 그룹 하나에는 알림을 받을 태스크를 하나만 지정할 수 있습니다.
+// `set_receiver()`/`set_waiter()`를 다시 호출하면 이전 태스크 대신 새 태스크로 바뀌며,
+// 여러 태스크가 같은 데이터를 기다려야 하면 받는 태스크가 다시 나눠 주어야 합니다.
+//
+#![cfg_attr(not(test), allow(dead_code))]
+
+use crate::hal::interrupt::{self, Mutex};
+use core::cell::RefCell;
+
+use crate::scheduler::TaskHandle;
+
+//
+// 메시지 큐
+//
+struct QueueInner<T: Copy, const N: usize> {
+    buffer: [Option<T>; N],
+    head: usize,
+    len: usize,
+    receiver: Option<(TaskHandle, u16)>,
+}
+
+/// 고정 크기 FIFO 메시지 큐
+///
+/// ```ignore
+/// static RX_QUEUE: ipc::Queue<u8, 32> = ipc::Queue::new();
+/// ```
+pub struct Queue<T: Copy, const N: usize>(Mutex<RefCell<QueueInner<T, N>>>);
+
+impl<T: Copy, const N: usize> Queue<T, N> {
+    pub const fn new() -> Self {
+        Self(Mutex::new(RefCell::new(QueueInner {
+            buffer: [None; N],
+            head: 0,
+            len: 0,
+            receiver: None,
+        })))
+    }
+
+    /// 데이터가 들어오면 `receiver`에 `bits` 알림을 보내도록 지정 (`None`이면 해제)
+    ///
+    /// 수신 태스크는 하나뿐이므로 이전에 지정한 태스크는 더 이상 알림을 받지 않습니다.
+    pub fn set_receiver(&self, receiver: Option<TaskHandle>, bits: u16) {
+        interrupt::free(|cs| {
+            self.0.borrow(cs).borrow_mut().receiver = receiver.map(|handle| (handle, bits));
+        });
+    }
+
+    /// 큐 끝에 추가. 가득 차 있으면 `Err(item)`을 그대로 돌려줍니다.
+    pub fn send(&self, item: T) -> Result<(), T> {
+        let receiver = interrupt::free(|cs| {
+            let mut queue = self.0.borrow(cs).borrow_mut();
+            if queue.len == N {
+                return Err(item);
+            }
+            let tail = (queue.head + queue.len) % N;
+            queue.buffer[tail] = Some(item);
+            queue.len += 1;
+            Ok(queue.receiver)
+        })?;
+
+        if let Some((handle, bits)) = receiver {
+            // 수신 태스크가 제거되었다면 알림만 생략
+            let _ = handle.notify(bits);
+        }
+        Ok(())
+    }
+
+    /// 큐 앞에서 하나 꺼냄 (비어 있으면 None)
+    pub fn receive(&self) -> Option<T> {
+        interrupt::free(|cs| {
+            let mut queue = self.0.borrow(cs).borrow_mut();
+            if queue.len == 0 {
+                return None;
+            }
+            let head = queue.head;
+            let item = queue.buffer[head].take();
+            queue.head = (head + 1) % N;
+            queue.len -= 1;
+            item
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        interrupt::free(|cs| self.0.borrow(cs).borrow().len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+}
+
+//
+// 이벤트 플래그
+//
+struct EventFlagsInner {
+    flags: u16,
+    waiter: Option<(TaskHandle, u16)>,
+}
+
+/// 16비트 이벤트 플래그 그룹
+///
+/// 대기 태스크를 `set_waiter(handle, mask)`로 지정하면, `mask`에 해당하는 비트가
+/// `set()`될 때 그 태스크에 같은 비트로 알림을 보냅니다.
+pub struct EventFlags(Mutex<RefCell<EventFlagsInner>>);
+
+impl EventFlags {
+    pub const fn new() -> Self {
+        Self(Mutex::new(RefCell::new(EventFlagsInner {
+            flags: 0,
+            waiter: None,
+        })))
+    }
+
+    /// `mask` 비트를 기다리는 태스크 지정 (`None`이면 해제)
+    ///
+    /// 대기 태스크는 하나뿐이므로 이전에 지정한 태스크(와 mask)는 대체됩니다.
+    pub fn set_waiter(&self, waiter: Option<TaskHandle>, mask: u16) {
+        interrupt::free(|cs| {
+            self.0.borrow(cs).borrow_mut().waiter = waiter.map(|handle| (handle, mask));
+        });
+    }
+
+    /// 플래그 비트 set (ISR에서 호출 가능)
+    pub fn set(&self, bits: u16) {
+        let wake = interrupt::free(|cs| {
+            let mut inner = self.0.borrow(cs).borrow_mut();
+            inner.flags |= bits;
+            inner
+                .waiter
+                .filter(|&(_, mask)| bits & mask != 0)
+                .map(|(handle, mask)| (handle, bits & mask))
+        });
+
+        if let Some((handle, bits)) = wake {
+            let _ = handle.notify(bits);
+        }
+    }
+
+    /// 플래그 비트 clear
+    pub fn clear(&self, bits: u16) {
+        interrupt::free(|cs| {
+            self.0.borrow(cs).borrow_mut().flags &= !bits;
+        });
+    }
+
+    /// 현재 플래그 값
+    pub fn get(&self) -> u16 {
+        interrupt::free(|cs| self.0.borrow(cs).borrow().flags)
+    }
+
+    /// `mask` 중 set된 비트를 읽고 지웁니다.
+    pub fn take(&self, mask: u16) -> u16 {
+        interrupt::free(|cs| {
+            let mut inner = self.0.borrow(cs).borrow_mut();
+            let bits = inner.flags & mask;
+            inner.flags &= !bits;
+            bits
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::{self, TaskState};
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    static RECEIVED: AtomicU32 = AtomicU32::new(0);
+
+    #[test]
+    fn queue_is_fifo_and_wraps_around() {
+        static QUEUE: Queue<u8, 3> = Queue::new();
+        let _guard = scheduler::tests::setup();
+        while QUEUE.receive().is_some() {}
+
+        for round in 0..3u8 {
+            assert_eq!(QUEUE.send(round * 10), Ok(()));
+            assert_eq!(QUEUE.send(round * 10 + 1), Ok(()));
+            assert_eq!(QUEUE.len(), 2);
+            assert_eq!(QUEUE.receive(), Some(round * 10));
+            assert_eq!(QUEUE.receive(), Some(round * 10 + 1));
+        }
+        assert!(QUEUE.is_empty());
+        assert_eq!(QUEUE.receive(), None);
+    }
+
+    #[test]
+    fn full_queue_returns_the_item() {
+        static QUEUE: Queue<u8, 2> = Queue::new();
+        let _guard = scheduler::tests::setup();
+        while QUEUE.receive().is_some() {}
+
+        QUEUE.send(1).unwrap();
+        QUEUE.send(2).unwrap();
+        assert!(QUEUE.is_full());
+        assert_eq!(QUEUE.send(3), Err(3));
+        assert_eq!(QUEUE.receive(), Some(1));
+        assert_eq!(QUEUE.send(3), Ok(()));
+    }
+
+    #[test]
+    fn send_wakes_the_receiver_task() {
+        static QUEUE: Queue<u8, 4> = Queue::new();
+        let _guard = scheduler::tests::setup();
+        RECEIVED.store(0, Ordering::SeqCst);
+
+        let receiver = scheduler::task_add_event(|| {
+            assert_eq!(scheduler::take_notification(), 0x02);
+            while let Some(byte) = QUEUE.receive() {
+                RECEIVED.fetch_add(byte as u32, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+        QUEUE.set_receiver(Some(receiver), 0x02);
+
+        QUEUE.send(5).unwrap();
+        QUEUE.send(7).unwrap();
+        assert_eq!(receiver.state(), Ok(TaskState::Ready));
+        scheduler::scheduler_run();
+        assert_eq!(RECEIVED.load(Ordering::SeqCst), 12);
+        assert!(QUEUE.is_empty());
+
+        // 해제 후에는 알림 없음
+        QUEUE.set_receiver(None, 0);
+        QUEUE.send(1).unwrap();
+        assert_eq!(receiver.state(), Ok(TaskState::Waiting));
+        QUEUE.receive();
+    }
+
+    #[test]
+    fn flags_wake_the_waiter_only_for_its_mask() {
+        static FLAGS: EventFlags = EventFlags::new();
+        let _guard = scheduler::tests::setup();
+        FLAGS.take(u16::MAX);
+        RECEIVED.store(0, Ordering::SeqCst);
+
+        let waiter = scheduler::task_add_event(|| {
+            RECEIVED.store(scheduler::take_notification() as u32, Ordering::SeqCst);
+        })
+        .unwrap();
+        FLAGS.set_waiter(Some(waiter), 0x0003);
+
+        // mask 밖의 비트는 기록만 되고 알림은 없음
+        FLAGS.set(0x0100);
+        assert_eq!(waiter.state(), Ok(TaskState::Waiting));
+        assert_eq!(FLAGS.get(), 0x0100);
+
+        FLAGS.set(0x0102);
+        scheduler::scheduler_run();
+        assert_eq!(RECEIVED.load(Ordering::SeqCst), 0x0002);
+
+        assert_eq!(FLAGS.take(0x00FF), 0x0002);
+        assert_eq!(FLAGS.get(), 0x0100);
+        FLAGS.clear(0x0100);
+        assert_eq!(FLAGS.get(), 0);
+    }
+
+    #[test]
+    fn setting_a_new_waiter_replaces_the_old_one() {
+        static FLAGS: EventFlags = EventFlags::new();
+        let _guard = scheduler::tests::setup();
+
+        let first = scheduler::task_add_event(|| {}).unwrap();
+        let second = scheduler::task_add_event(|| {}).unwrap();
+        FLAGS.set_waiter(Some(first), 0x0001);
+        FLAGS.set_waiter(Some(second), 0x0001);
+
+        FLAGS.set(0x0001);
+        assert_eq!(first.state(), Ok(TaskState::Waiting));
+        assert_eq!(second.state(), Ok(TaskState::Ready));
+    }
+}
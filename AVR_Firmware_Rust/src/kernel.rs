@@ -0,0 +1,412 @@
+
+//
+// 선점형(preemptive) 멀티태스킹 커널 (`preemptive` 기능)
+//
+// - TIMER0_COMPA(tick 인터럽트)에서 전체 레지스터 문맥을 저장하고, 가장 높은 우선순위의
+//   실행 가능한 스레드로 스택 포인터를 교체합니다. (같은 우선순위는 라운드 로빈)
+// - 각 스레드는 정적으로 할당한 `Stack<N>`을 사용하며, 스택 바닥에 canary를 두어
+//   문맥 전환 시마다 overflow를 검사합니다.
+// - `start()`를 호출한 문맥(보통 main의 `scheduler_run()` 루프)은 우선순위 0의
+//   메인 스레드가 되며, 다른 스레드가 모두 잠들어 있으면 이 스레드가 실행됩니다.
+//
+// 저장되는 문맥 (ATmega2560, 3바이트 PC):
+//   PC(3) → r0 → SREG → RAMPZ → EIND → r1 ~ r31   (총 38바이트)
+//
+#![cfg_attr(not(test), allow(dead_code))]
+
+use core::arch::naked_asm;
+use core::cell::{Cell, RefCell, UnsafeCell};
+
+use avr_device::interrupt::{self, Mutex};
+
+use crate::scheduler::{self, time_reached};
+
+//
+// 커널 설정
+//
+/// 메인 스레드를 포함한 최대 스레드 수
+const MAX_THREADS: usize = 4;
+/// 스택 바닥에 채워 두는 canary 값 / 검사 바이트 수
+const STACK_CANARY: u8 = 0xA5;
+const CANARY_BYTES: usize = 4;
+/// 스레드 하나가 우선순위를 상속받은 채 동시에 가질 수 있는 락 수
+const MAX_INHERITED: usize = 4;
+/// 스레드 문맥 저장에 필요한 바이트 수
+const CONTEXT_SIZE: usize = 38;
+/// `Stack<N>`의 최소 크기 (문맥 + ISR 중첩 여유)
+pub const MIN_STACK_SIZE: usize = 96;
+
+// 문맥 전환 ISR은 TIMER0_COMPA(__vector_21)에 고정되어 있음
+#[cfg(any(feature = "tick-timer1", feature = "tick-timer2"))]
+compile_error!("the `preemptive` kernel requires the Timer0 tick (remove `tick-timer1` / `tick-timer2`)");
+
+/// 커널 API 오류
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KernelError {
+    /// 스레드 테이블에 빈 슬롯이 없음
+    TooManyThreads,
+    /// 이미 다른 스레드가 사용 중인 스택
+    StackInUse,
+}
+
+/// 스레드 번호 (0 = 메인 스레드)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ThreadId(pub u8);
+
+/// 스레드용 정적 스택
+///
+/// ```ignore
+/// static WORKER_STACK: kernel::Stack<256> = kernel::Stack::new();
+/// kernel::spawn(worker, &WORKER_STACK, 2).unwrap();
+/// ```
+pub struct Stack<const N: usize> {
+    memory: UnsafeCell<[u8; N]>,
+    taken: Mutex<Cell<bool>>,
+}
+
+// 메모리는 spawn 시 한 번 초기화된 뒤 해당 스레드만 사용합니다.
+unsafe impl<const N: usize> Sync for Stack<N> {}
+
+impl<const N: usize> Stack<N> {
+    pub const fn new() -> Self {
+        Self {
+            memory: UnsafeCell::new([0; N]),
+            taken: Mutex::new(Cell::new(false)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum ThreadState {
+    Ready,
+    Sleeping { until: u32 },
+}
+
+#[derive(Copy, Clone)]
+struct Thread {
+    /// 저장된 스택 포인터 (다음 push 위치)
+    sp: u16,
+    /// 스택 최하위 주소 (메인 스레드는 0: canary 없음)
+    stack_bottom: u16,
+    stack_size: u16,
+    /// 실행 우선순위 (우선순위 상속 중이면 base_priority보다 높음)
+    priority: u8,
+    /// spawn 시 지정한 원래 우선순위
+    base_priority: u8,
+    /// 가진 락별로 상속받은 우선순위 (락 주소, 우선순위)
+    inherited: [Option<(usize, u8)>; MAX_INHERITED],
+    state: ThreadState,
+}
+
+static THREADS: Mutex<RefCell<[Option<Thread>; MAX_THREADS]>> =
+    Mutex::new(RefCell::new([None; MAX_THREADS]));
+static CURRENT: Mutex<Cell<usize>> = Mutex::new(Cell::new(0));
+static STARTED: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+
+/// 스레드를 생성합니다. `start()` 이후 다음 tick부터 스케줄링됩니다.
+///
+/// - `entry`: 스레드 본체 (반환하지 않음)
+/// - `priority`: 값이 클수록 먼저 실행 (메인 스레드는 0)
+pub fn spawn<const N: usize>(
+    entry: fn() -> !,
+    stack: &'static Stack<N>,
+    priority: u8,
+) -> Result<ThreadId, KernelError> {
+    const {
+        assert!(N >= MIN_STACK_SIZE, "thread stack is smaller than kernel::MIN_STACK_SIZE");
+    }
+
+    interrupt::free(|cs| {
+        let mut threads = THREADS.borrow(cs).borrow_mut();
+        // 슬롯 0은 메인 스레드용
+        let index = (1..MAX_THREADS)
+            .find(|&i| threads[i].is_none())
+            .ok_or(KernelError::TooManyThreads)?;
+        if stack.taken.borrow(cs).replace(true) {
+            return Err(KernelError::StackInUse);
+        }
+
+        let memory = unsafe { &mut *stack.memory.get() };
+        let sp = init_stack(memory, entry);
+        threads[index] = Some(Thread {
+            sp,
+            stack_bottom: memory.as_ptr() as u16,
+            stack_size: N as u16,
+            priority,
+            base_priority: priority,
+            inherited: [None; MAX_INHERITED],
+            state: ThreadState::Ready,
+        });
+        Ok(ThreadId(index as u8))
+    })
+}
+
+/// 새 스레드의 초기 문맥을 스택에 만들고 저장할 SP를 반환
+fn init_stack(memory: &mut [u8], entry: fn() -> !) -> u16 {
+    memory.fill(STACK_CANARY);
+
+    // AVR 스택은 높은 주소에서 낮은 주소로 자람 (push: 쓰고 나서 SP 감소)
+    let mut top = memory.len() - 1;
+    let mut push = |value: u8| {
+        memory[top] = value;
+        top -= 1;
+    };
+
+    // reti로 돌아갈 주소 (워드 주소, 하위 바이트부터)
+    let pc = entry as usize as u16;
+    push(pc as u8);
+    push((pc >> 8) as u8);
+    push(0);
+
+    push(0x00); // r0
+    push(0x80); // SREG (I 비트 set)
+    push(0x00); // RAMPZ
+    push(0x00); // EIND
+    for _ in 1..=31 {
+        push(0x00); // r1 ~ r31 (r1은 0이어야 함)
+    }
+
+    let sp = memory.as_ptr() as u16 + top as u16;
+    debug_assert!(memory.len() - 1 - top == CONTEXT_SIZE);
+    sp
+}
+
+/// 현재 문맥을 메인 스레드로 등록하고 선점형 스케줄링을 시작합니다.
+pub fn start() {
+    interrupt::free(|cs| {
+        THREADS.borrow(cs).borrow_mut()[0] = Some(Thread {
+            sp: 0, // 첫 문맥 전환 시 저장됨
+            stack_bottom: 0,
+            stack_size: 0,
+            priority: 0,
+            base_priority: 0,
+            inherited: [None; MAX_INHERITED],
+            state: ThreadState::Ready,
+        });
+        CURRENT.borrow(cs).set(0);
+        STARTED.borrow(cs).set(true);
+    });
+}
+
+/// 현재 실행 중인 스레드
+pub fn current() -> ThreadId {
+    interrupt::free(|cs| ThreadId(CURRENT.borrow(cs).get() as u8))
+}
+
+/// 스레드의 현재 실행 우선순위 (상속받은 우선순위 포함)
+pub fn priority(id: ThreadId) -> Option<u8> {
+    interrupt::free(|cs| THREADS.borrow(cs).borrow()[id.0 as usize].map(|thread| thread.priority))
+}
+
+/// 우선순위 상속: `id` 스레드가 가진 락 `lock`을 통해 실행 우선순위를 최소 `priority`로 올립니다.
+/// (`sync::Mutex`가 사용)
+///
+/// 락별로 기록해 두므로 `MAX_INHERITED`개를 넘는 락에는 상속하지 않고 `false`를 반환합니다.
+pub(crate) fn inherit_priority(id: ThreadId, lock: usize, priority: u8) -> bool {
+    interrupt::free(|cs| {
+        let mut threads = THREADS.borrow(cs).borrow_mut();
+        let Some(thread) = threads[id.0 as usize].as_mut() else {
+            return false;
+        };
+        let entry = match thread.inherited.iter().position(|entry| matches!(entry, Some((key, _)) if *key == lock)) {
+            Some(index) => &mut thread.inherited[index],
+            None => match thread.inherited.iter_mut().find(|entry| entry.is_none()) {
+                Some(entry) => entry,
+                None => return false,
+            },
+        };
+        let inherited = entry.map_or(priority, |(_, old)| old.max(priority));
+        *entry = Some((lock, inherited));
+        thread.priority = thread.priority.max(inherited);
+        true
+    })
+}
+
+/// 락 `lock`을 통해 상속받은 우선순위를 해제합니다.
+///
+/// 아직 가진 다른 락으로 상속받은 우선순위가 있으면 그중 가장 높은 값으로,
+/// 없으면 원래 우선순위로 돌아갑니다.
+pub(crate) fn restore_priority(id: ThreadId, lock: usize) {
+    interrupt::free(|cs| {
+        if let Some(thread) = THREADS.borrow(cs).borrow_mut()[id.0 as usize].as_mut() {
+            for entry in thread.inherited.iter_mut() {
+                if matches!(entry, Some((key, _)) if *key == lock) {
+                    *entry = None;
+                }
+            }
+            thread.priority = thread
+                .inherited
+                .iter()
+                .flatten()
+                .fold(thread.base_priority, |priority, &(_, inherited)| priority.max(inherited));
+        }
+    });
+}
+
+/// 현재 스레드를 `ms` 동안 재웁니다. 그동안 다른 스레드가 실행됩니다.
+pub fn sleep(ms: u32) {
+    let until = scheduler::millis().wrapping_add(ms);
+    while !time_reached(scheduler::millis(), until) {
+        interrupt::free(|cs| {
+            let index = CURRENT.borrow(cs).get();
+            if let Some(thread) = THREADS.borrow(cs).borrow_mut()[index].as_mut() {
+                thread.state = ThreadState::Sleeping { until };
+            }
+        });
+        yield_now();
+    }
+}
+
+/// 남은 time slice를 양보하고 다른 스레드로 전환합니다.
+pub fn yield_now() {
+    unsafe { kernel_yield() };
+}
+
+/// 스레드 스택의 최대 사용량(바이트). canary로 채운 영역 중 덮어쓰인 양으로 계산.
+pub fn stack_high_water(id: ThreadId) -> Option<u16> {
+    interrupt::free(|cs| {
+        let thread = THREADS.borrow(cs).borrow()[id.0 as usize]?;
+        if thread.stack_bottom == 0 {
+            return None;
+        }
+        let memory = unsafe {
+            core::slice::from_raw_parts(thread.stack_bottom as *const u8, thread.stack_size as usize)
+        };
+        let untouched = memory.iter().take_while(|&&b| b == STACK_CANARY).count();
+        Some(thread.stack_size - untouched as u16)
+    })
+}
+
+/// 스택 바닥의 canary가 손상되었는지 검사
+fn stack_overflowed(thread: &Thread) -> bool {
+    if thread.stack_bottom == 0 {
+        return false;
+    }
+    let bottom = thread.stack_bottom as *const u8;
+    (0..CANARY_BYTES).any(|i| unsafe { bottom.add(i).read_volatile() } != STACK_CANARY)
+}
+
+/// 다음에 실행할 스레드 선택 (가장 높은 우선순위, 같으면 현재 다음 순서부터)
+fn pick_next(threads: &mut [Option<Thread>; MAX_THREADS], current: usize) -> usize {
+    let now = scheduler::millis();
+
+    let mut best: Option<(usize, u8)> = None;
+    for offset in 1..=MAX_THREADS {
+        let index = (current + offset) % MAX_THREADS;
+        let Some(thread) = threads[index].as_mut() else {
+            continue;
+        };
+        if let ThreadState::Sleeping { until } = thread.state {
+            if !time_reached(now, until) {
+                continue;
+            }
+            thread.state = ThreadState::Ready;
+        }
+        if best.is_none_or(|(_, priority)| thread.priority > priority) {
+            best = Some((index, thread.priority));
+        }
+    }
+
+    // 모든 스레드가 잠들어 있으면 메인 스레드가 idle 역할을 함
+    best.map_or(0, |(index, _)| index)
+}
+
+/// 문맥 전환 본체: 현재 SP를 저장하고 다음 스레드의 SP를 반환
+///
+/// `tick`이 true이면 TIMER0_COMPA에서 호출된 것이므로 스케줄러 tick도 처리합니다.
+extern "C" fn switch_context(sp: u16, tick: bool) -> u16 {
+    interrupt::free(|cs| {
+        if tick {
+            scheduler::tick(cs);
+        }
+        if !STARTED.borrow(cs).get() {
+            return sp;
+        }
+
+        let mut threads = THREADS.borrow(cs).borrow_mut();
+        let current = CURRENT.borrow(cs).get();
+        if let Some(thread) = threads[current].as_mut() {
+            thread.sp = sp;
+            if stack_overflowed(thread) {
+                panic!("kernel: stack overflow in thread {}", current);
+            }
+        }
+
+        let next = pick_next(&mut threads, current);
+        CURRENT.borrow(cs).set(next);
+        threads[next].map_or(sp, |thread| thread.sp)
+    })
+}
+
+//
+// 문맥 저장/복원 어셈블리
+//
+macro_rules! save_context {
+    () => {
+        concat!(
+            "push r0\n",
+            "in r0, 0x3f\n", // SREG
+            "cli\n",
+            "push r0\n",
+            "in r0, 0x3b\n", // RAMPZ
+            "push r0\n",
+            "in r0, 0x3c\n", // EIND
+            "push r0\n",
+            "push r1\n",
+            "clr r1\n",
+            "push r2\npush r3\npush r4\npush r5\npush r6\npush r7\npush r8\npush r9\n",
+            "push r10\npush r11\npush r12\npush r13\npush r14\npush r15\npush r16\npush r17\n",
+            "push r18\npush r19\npush r20\npush r21\npush r22\npush r23\npush r24\npush r25\n",
+            "push r26\npush r27\npush r28\npush r29\npush r30\npush r31\n",
+            "in r24, 0x3d\n", // SPL
+            "in r25, 0x3e\n", // SPH
+        )
+    };
+}
+
+macro_rules! restore_context {
+    () => {
+        concat!(
+            "out 0x3e, r25\n", // SPH
+            "out 0x3d, r24\n", // SPL
+            "pop r31\npop r30\npop r29\npop r28\npop r27\npop r26\n",
+            "pop r25\npop r24\npop r23\npop r22\npop r21\npop r20\npop r19\npop r18\n",
+            "pop r17\npop r16\npop r15\npop r14\npop r13\npop r12\npop r11\npop r10\n",
+            "pop r9\npop r8\npop r7\npop r6\npop r5\npop r4\npop r3\npop r2\n",
+            "pop r1\n",
+            "pop r0\n",
+            "out 0x3c, r0\n", // EIND
+            "pop r0\n",
+            "out 0x3b, r0\n", // RAMPZ
+            "pop r0\n",
+            "out 0x3f, r0\n", // SREG
+            "pop r0\n",
+            "reti\n",
+        )
+    };
+}
+
+/// TIMER0_COMPA (__vector_21): tick 처리 + 선점형 문맥 전환
+#[unsafe(naked)]
+#[unsafe(no_mangle)]
+pub unsafe extern "avr-interrupt" fn __vector_21() {
+    naked_asm!(
+        save_context!(),
+        "ldi r22, 1",
+        "call {switch}",
+        restore_context!(),
+        switch = sym switch_context,
+    )
+}
+
+/// 자발적 문맥 전환 (tick 처리 없음). reti로 복귀하므로 인터럽트는 다시 허용됩니다.
+#[unsafe(naked)]
+unsafe extern "C" fn kernel_yield() {
+    naked_asm!(
+        save_context!(),
+        "ldi r22, 0",
+        "call {switch}",
+        restore_context!(),
+        switch = sym switch_context,
+    )
+}
@@ -1,18 +1,31 @@
-#![feature(asm_experimental_arch)]
-#![feature(abi_avr_interrupt)]
+#![cfg_attr(target_arch = "avr", feature(asm_experimental_arch, abi_avr_interrupt))]
 
-#![no_std]
-#![no_main]
+// 호스트(x86) 빌드는 std + 시뮬레이션 백엔드(`hal::sim`)를 사용 (`cargo test`, 예제 main 타입 검사)
+#![cfg_attr(target_arch = "avr", no_std)]
+#![cfg_attr(target_arch = "avr", no_main)]
 
+// 라이브러리 모듈은 `#![cfg_attr(not(test), allow(dead_code))]`: 예제 main이 쓰지 않는 API는
+// 펌웨어 빌드에서 경고하지 않고, 테스트 빌드(`cargo test-host`)에서 쓰이지 않는 코드를 잡음
+mod hal;
 mod scheduler;
+mod tick;
+mod executor;
+mod ipc;
+mod sync;
+mod watchdog;
+mod trace;
+#[cfg(all(feature = "preemptive", target_arch = "avr"))]
+mod kernel;
 mod port;
+mod gpio;
 mod serial;
 mod arduino;
 
+#[cfg(target_arch = "avr")]
 use panic_halt as _;
+#[cfg(target_arch = "avr")]
 use avr_device::entry;
-use avr_device::atmega2560;
-use port::*;
+use hal::pac;
 use arduino::*;
 use arduino::PinMode::*;
 
@@ -41,20 +54,27 @@ fn user_task_4() {
 
 
 /// 메인 함수 (실제 엔트리 포인트)
-#[entry]
+#[cfg_attr(target_arch = "avr", entry)]
 fn main() -> ! {
-    let dp = atmega2560::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
 
-    // 1) 타이머 초기화(Timer0)
+    // 1) 타이머 초기화 (`tick-timer*` 기능으로 선택한 타이머)
+    #[cfg(not(any(feature = "tick-timer1", feature = "tick-timer2")))]
     scheduler::timer_init(dp.TC0);
+    #[cfg(feature = "tick-timer1")]
+    scheduler::timer_init(dp.TC1);
+    #[cfg(feature = "tick-timer2")]
+    scheduler::timer_init(dp.TC2);
     // 2) serial 초기화
     serial::serial_init(dp.USART0, 115200);
 
     // 2) 태스크 등록 (예: 100ms, 500ms 주기)
-    scheduler::task_add(user_task_1, 1000);
-    scheduler::task_add(user_task_2, 10);
-    scheduler::task_add(user_task_3, 2);
-    scheduler::task_add(user_task_4, 0);
+    //    첫 실행 시각을 자동 분산해 여러 태스크가 같은 tick에 몰리지 않게 함
+    scheduler::set_auto_stagger(true);
+    scheduler::task_add(user_task_1, 1000).unwrap();
+    scheduler::task_add(user_task_2, 10).unwrap();
+    scheduler::task_add(user_task_3, 2).unwrap();
+    scheduler::task_add(user_task_4, 0).unwrap();
 
     // PORTB.set_pin_output(7);
     pin_mode(LED_BUILTIN, Output);
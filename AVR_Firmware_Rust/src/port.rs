@@ -1,68 +1,219 @@
-use core::ptr::{read_volatile, write_volatile};
+#![cfg_attr(not(test), allow(dead_code))]
+
+use crate::hal::{self, interrupt};
+
+//
+// 레지스터 접근 계층
+//
+// `Port`는 레지스터를 직접 읽고 쓰지 않고 `RegisterAccess`를 통해 접근합니다.
+// - `Mmio`: 기본 구현. `hal`을 그대로 사용 (AVR: 실제 레지스터, 호스트: `hal::sim` 가상 레지스터 파일)
+// - 테스트에서는 접근을 기록하는 mock을 `Port::with_registers()`로 끼워 넣어
+//   "어떤 레지스터의 어떤 비트를 건드렸는지"를 검사할 수 있습니다. (아래 `mock::RecordingRegisters`)
+//
+/// 데이터 공간 레지스터 접근
+///
+/// # Safety
+/// 구현은 `addr`이 `Port`가 가진 PINx/DDRx/PORTx 주소일 때만 호출됩니다.
+/// 실제 하드웨어 구현은 `set_bit` / `clear_bit`이 인터럽트에 안전해야 합니다.
+pub trait RegisterAccess {
+    /// # Safety
+    /// `addr`은 유효한 I/O 레지스터 주소여야 합니다.
+    unsafe fn read(&self, addr: *mut u8) -> u8;
+    /// # Safety
+    /// `addr`은 유효한 I/O 레지스터 주소여야 합니다.
+    unsafe fn write(&self, addr: *mut u8, value: u8);
+    /// # Safety
+    /// `addr`은 유효한 I/O 레지스터 주소여야 합니다.
+    unsafe fn set_bit(&self, addr: *mut u8, bit: u8);
+    /// # Safety
+    /// `addr`은 유효한 I/O 레지스터 주소여야 합니다.
+    unsafe fn clear_bit(&self, addr: *mut u8, bit: u8);
+}
+
+/// 실제 메모리 맵 I/O (`hal`의 레지스터 함수)
+#[derive(Copy, Clone)]
+pub struct Mmio;
+
+impl RegisterAccess for Mmio {
+    #[inline(always)]
+    unsafe fn read(&self, addr: *mut u8) -> u8 {
+        unsafe { hal::read_reg(addr) }
+    }
+
+    #[inline(always)]
+    unsafe fn write(&self, addr: *mut u8, value: u8) {
+        unsafe { hal::write_reg(addr, value) }
+    }
+
+    #[inline(always)]
+    unsafe fn set_bit(&self, addr: *mut u8, bit: u8) {
+        unsafe { hal::set_reg_bit(addr, bit) }
+    }
+
+    #[inline(always)]
+    unsafe fn clear_bit(&self, addr: *mut u8, bit: u8) {
+        unsafe { hal::clear_reg_bit(addr, bit) }
+    }
+}
+
+impl<R: RegisterAccess> RegisterAccess for &R {
+    unsafe fn read(&self, addr: *mut u8) -> u8 {
+        unsafe { (**self).read(addr) }
+    }
+
+    unsafe fn write(&self, addr: *mut u8, value: u8) {
+        unsafe { (**self).write(addr, value) }
+    }
+
+    unsafe fn set_bit(&self, addr: *mut u8, bit: u8) {
+        unsafe { (**self).set_bit(addr, bit) }
+    }
+
+    unsafe fn clear_bit(&self, addr: *mut u8, bit: u8) {
+        unsafe { (**self).clear_bit(addr, bit) }
+    }
+}
+
+//
+// 포트
+//
 
 /// Port 구조체:
-/// - `pin`  : PINx 레지스터 주소
+/// - `pin`  : PINx 레지스터 주소 (데이터 공간)
 /// - `ddr`  : DDRx 레지스터 주소
 /// - `port` : PORTx 레지스터 주소
+/// - `regs` : 레지스터 접근 방법 (기본 `Mmio`)
 ///
 /// 해당 구조체의 메서드를 통해 핀 방향 설정/출력/입력 읽기 등의 작업을 수행합니다.
-pub struct Port {
-    pin: *mut u8,
-    ddr: *mut u8,
-    port: *mut u8,
+///
+/// 모든 쓰기 메서드는 인터럽트에 안전합니다. 메서드 도중에 ISR이 같은 포트의 다른 비트를
+/// 바꿔도 그 변경이 덮어써지지 않습니다.
+/// - 한 비트/여러 비트(마스크) 변경: 임계구역 안에서 read-modify-write
+///   (핀 번호가 실행 중에 정해지므로 SBI/CBI를 쓸 수 없음. 상수 핀은 `gpio::Pin` 사용)
+/// - 토글: 모든 포트에서 PINx에 1을 쓰는 한 번의 쓰기
+pub struct Port<R = Mmio> {
+    pin: usize,
+    ddr: usize,
+    port: usize,
+    regs: R,
+}
+
+impl<R> Port<R> {
+    /// PINx 데이터 공간 주소 (상수 문맥에서 사용 가능)
+    pub const fn pin_addr(&self) -> usize {
+        self.pin
+    }
+
+    /// DDRx 데이터 공간 주소
+    pub const fn ddr_addr(&self) -> usize {
+        self.ddr
+    }
+
+    /// PORTx 데이터 공간 주소
+    pub const fn port_addr(&self) -> usize {
+        self.port
+    }
 }
 
-unsafe impl Sync for Port {}
-unsafe impl Send for Port {}
+impl<R: RegisterAccess> Port<R> {
+    /// 같은 레지스터 주소를 다른 접근 방법으로 다루는 포트 (테스트용 mock 주입)
+    pub fn with_registers<S: RegisterAccess>(&self, regs: S) -> Port<S> {
+        Port { pin: self.pin, ddr: self.ddr, port: self.port, regs }
+    }
 
-impl Port {
     /// 지정한 비트(pin_number)를 출력으로 설정합니다. (DDRx |= (1 << pin_number))
     pub fn set_pin_output(&self, pin_number: u8) {
-        unsafe {
-            let val = read_volatile(self.ddr);
-            write_volatile(self.ddr, val | (1 << pin_number));
-        }
+        unsafe { self.regs.set_bit(self.ddr as *mut u8, pin_number) }
     }
 
     /// 지정한 비트(pin_number)를 입력으로 설정합니다. (DDRx &= ~(1 << pin_number))
     pub fn set_pin_input(&self, pin_number: u8) {
-        unsafe {
-            let val = read_volatile(self.ddr);
-            write_volatile(self.ddr, val & !(1 << pin_number));
-        }
+        unsafe { self.regs.clear_bit(self.ddr as *mut u8, pin_number) }
     }
 
     /// 지정한 비트(pin_number)를 High(1)로 설정합니다. (PORTx |= (1 << pin_number))
     pub fn set_pin_high(&self, pin_number: u8) {
-        unsafe {
-            let val = read_volatile(self.port);
-            write_volatile(self.port, val | (1 << pin_number));
-        }
+        unsafe { self.regs.set_bit(self.port as *mut u8, pin_number) }
     }
 
     /// 지정한 비트(pin_number)를 Low(0)로 설정합니다. (PORTx &= ~(1 << pin_number))
     pub fn set_pin_low(&self, pin_number: u8) {
-        unsafe {
-            let val = read_volatile(self.port);
-            write_volatile(self.port, val & !(1 << pin_number));
-        }
+        unsafe { self.regs.clear_bit(self.port as *mut u8, pin_number) }
     }
 
-    /// 지정한 비트(pin_number)를 토글합니다. (PORTx ^= (1 << pin_number))
+    /// 지정한 비트(pin_number)를 토글합니다. (PINx = (1 << pin_number))
+    ///
+    /// PINx에 1을 쓰면 하드웨어가 PORTx의 해당 비트를 뒤집으므로 읽기 없이 한 번의 쓰기로 끝납니다.
     pub fn toggle_pin(&self, pin_number: u8) {
+        unsafe { self.regs.write(self.pin as *mut u8, 1 << pin_number) }
+    }
+
+    /// 지정한 비트(pin_number)의 출력값(PORTx)을 읽어 반환합니다. (true = High, false = Low)
+    pub fn read_output_pin(&self, pin_number: u8) -> bool {
         unsafe {
-            let val = read_volatile(self.port);
-            write_volatile(self.port, val ^ (1 << pin_number));
+            let val = self.regs.read(self.port as *mut u8);
+            (val & (1 << pin_number)) != 0
         }
     }
 
     /// 지정한 비트(pin_number)의 입력값(PINx)을 읽어 반환합니다. (true = High, false = Low)
     pub fn read_pin(&self, pin_number: u8) -> bool {
         unsafe {
-            let val = read_volatile(self.pin);
+            let val = self.regs.read(self.pin as *mut u8);
             (val & (1 << pin_number)) != 0
         }
     }
+
+    //
+    // 포트 전체(8비트) 입출력 — 8비트 병렬 버스(LCD 데이터선, R-2R DAC 등)용
+    //
+    /// 포트 8개 핀의 입력값(PINx)을 한 번에 읽습니다.
+    pub fn read(&self) -> u8 {
+        unsafe { self.regs.read(self.pin as *mut u8) }
+    }
+
+    /// 포트 8개 핀의 출력값(PORTx)을 한 번에 씁니다. (PORTx = value)
+    ///
+    /// 출력 핀은 한 명령에 동시에 바뀝니다. 입력 핀에서는 해당 비트가 풀업 설정이 됩니다.
+    pub fn write(&self, value: u8) {
+        unsafe { self.regs.write(self.port as *mut u8, value) }
+    }
+
+    /// 현재 출력값(PORTx)을 읽습니다.
+    pub fn read_output(&self) -> u8 {
+        unsafe { self.regs.read(self.port as *mut u8) }
+    }
+
+    /// `mask`의 비트들을 출력으로 설정합니다. (DDRx |= mask)
+    pub fn set_output_mask(&self, mask: u8) {
+        self.modify_reg(self.ddr as *mut u8, mask, 0xFF)
+    }
+
+    /// `mask`의 비트들을 입력으로 설정합니다. (DDRx &= ~mask)
+    pub fn set_input_mask(&self, mask: u8) {
+        self.modify_reg(self.ddr as *mut u8, mask, 0x00)
+    }
+
+    /// `mask`의 비트만 `value`로 바꾸고 나머지 출력은 그대로 둡니다.
+    /// (PORTx = (PORTx & ~mask) | (value & mask))
+    ///
+    /// 예: PORTC 하위 4비트에 니블 출력 → `PORTC.modify(0x0F, nibble)`
+    pub fn modify(&self, mask: u8, value: u8) {
+        self.modify_reg(self.port as *mut u8, mask, value)
+    }
+
+    /// `mask`의 비트들을 한 번에 토글합니다. (PINx = mask)
+    pub fn toggle_mask(&self, mask: u8) {
+        unsafe { self.regs.write(self.pin as *mut u8, mask) }
+    }
+
+    /// 여러 비트를 한 번에 바꾸므로 임계구역 안에서 read-modify-write 합니다.
+    fn modify_reg(&self, addr: *mut u8, mask: u8, value: u8) {
+        interrupt::free(|_| unsafe {
+            let old = self.regs.read(addr);
+            self.regs.write(addr, (old & !mask) | (value & mask));
+        })
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -74,68 +225,241 @@ impl Port {
 //       아래는 대표적인 매핑 예시이며, 반드시 공식 데이터시트로 재확인하세요.
 //------------------------------------------------------------------------------
 pub const PORTA: Port = Port {
-    pin: 0x20 as *mut u8,  // PINA
-    ddr: 0x21 as *mut u8,  // DDRA
-    port: 0x22 as *mut u8, // PORTA
+    pin: 0x20,  // PINA
+    ddr: 0x21,  // DDRA
+    port: 0x22, // PORTA
+    regs: Mmio,
 };
 
 pub const PORTB: Port = Port {
-    pin: 0x23 as *mut u8,  // PINB
-    ddr: 0x24 as *mut u8,  // DDRB
-    port: 0x25 as *mut u8, // PORTB
+    pin: 0x23,  // PINB
+    ddr: 0x24,  // DDRB
+    port: 0x25, // PORTB
+    regs: Mmio,
 };
 
 pub const PORTC: Port = Port {
-    pin: 0x26 as *mut u8,  // PINC
-    ddr: 0x27 as *mut u8,  // DDRC
-    port: 0x28 as *mut u8, // PORTC
+    pin: 0x26,  // PINC
+    ddr: 0x27,  // DDRC
+    port: 0x28, // PORTC
+    regs: Mmio,
 };
 
 pub const PORTD: Port = Port {
-    pin: 0x29 as *mut u8,  // PIND
-    ddr: 0x2A as *mut u8,  // DDRD
-    port: 0x2B as *mut u8, // PORTD
+    pin: 0x29,  // PIND
+    ddr: 0x2A,  // DDRD
+    port: 0x2B, // PORTD
+    regs: Mmio,
 };
 
 pub const PORTE: Port = Port {
-    pin: 0x2C as *mut u8,  // PINE
-    ddr: 0x2D as *mut u8,  // DDRE
-    port: 0x2E as *mut u8, // PORTE
+    pin: 0x2C,  // PINE
+    ddr: 0x2D,  // DDRE
+    port: 0x2E, // PORTE
+    regs: Mmio,
 };
 
 pub const PORTF: Port = Port {
-    pin: 0x2F as *mut u8,  // PINF
-    ddr: 0x30 as *mut u8,  // DDRF
-    port: 0x31 as *mut u8, // PORTF
+    pin: 0x2F,  // PINF
+    ddr: 0x30,  // DDRF
+    port: 0x31, // PORTF
+    regs: Mmio,
 };
 
 pub const PORTG: Port = Port {
-    pin: 0x32 as *mut u8,  // PING
-    ddr: 0x33 as *mut u8,  // DDRG
-    port: 0x34 as *mut u8, // PORTG
+    pin: 0x32,  // PING
+    ddr: 0x33,  // DDRG
+    port: 0x34, // PORTG
+    regs: Mmio,
 };
 
 // ATmega2560에서 H~L 포트는 IO 공간 주소가 0x100 이상으로 표시됩니다.
 pub const PORTH: Port = Port {
-    pin: 0x100 as *mut u8,  // PINH
-    ddr: 0x101 as *mut u8,  // DDRH
-    port: 0x102 as *mut u8, // PORTH
+    pin: 0x100,  // PINH
+    ddr: 0x101,  // DDRH
+    port: 0x102, // PORTH
+    regs: Mmio,
 };
 
 pub const PORTJ: Port = Port {
-    pin: 0x103 as *mut u8,  // PINJ
-    ddr: 0x104 as *mut u8,  // DDRJ
-    port: 0x105 as *mut u8, // PORTJ
+    pin: 0x103,  // PINJ
+    ddr: 0x104,  // DDRJ
+    port: 0x105, // PORTJ
+    regs: Mmio,
 };
 
 pub const PORTK: Port = Port {
-    pin: 0x106 as *mut u8,  // PINK
-    ddr: 0x107 as *mut u8,  // DDRK
-    port: 0x108 as *mut u8, // PORTK
+    pin: 0x106,  // PINK
+    ddr: 0x107,  // DDRK
+    port: 0x108, // PORTK
+    regs: Mmio,
 };
 
 pub const PORTL: Port = Port {
-    pin: 0x109 as *mut u8,  // PINL
-    ddr: 0x10A as *mut u8,  // DDRL
-    port: 0x10B as *mut u8, // PORTL
+    pin: 0x109,  // PINL
+    ddr: 0x10A,  // DDRL
+    port: 0x10B, // PORTL
+    regs: Mmio,
 };
+
+//
+// 테스트용 접근 기록 mock
+//
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::RegisterAccess;
+    use std::cell::RefCell;
+    use std::vec::Vec;
+
+    /// 기록된 레지스터 접근 (주소는 데이터 공간 주소)
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub enum RegisterOp {
+        Read(usize),
+        Write(usize, u8),
+        SetBit(usize, u8),
+        ClearBit(usize, u8),
+    }
+
+    /// 레지스터 값을 메모리에 두고, 모든 접근을 순서대로 기록하는 mock
+    pub struct RecordingRegisters {
+        values: RefCell<[u8; 0x200]>,
+        log: RefCell<Vec<RegisterOp>>,
+    }
+
+    impl RecordingRegisters {
+        pub fn new() -> Self {
+            RecordingRegisters { values: RefCell::new([0; 0x200]), log: RefCell::new(Vec::new()) }
+        }
+
+        /// 현재 레지스터 값
+        pub fn value(&self, addr: usize) -> u8 {
+            self.values.borrow()[addr]
+        }
+
+        /// 레지스터 값을 기록 없이 설정 (입력 흉내)
+        pub fn set_value(&self, addr: usize, value: u8) {
+            self.values.borrow_mut()[addr] = value;
+        }
+
+        /// 지금까지의 접근 기록을 꺼내고 비움
+        pub fn take_log(&self) -> Vec<RegisterOp> {
+            self.log.take()
+        }
+
+        fn update(&self, addr: usize, op: RegisterOp, f: impl FnOnce(u8) -> u8) {
+            let mut values = self.values.borrow_mut();
+            values[addr] = f(values[addr]);
+            self.log.borrow_mut().push(op);
+        }
+    }
+
+    impl RegisterAccess for RecordingRegisters {
+        unsafe fn read(&self, addr: *mut u8) -> u8 {
+            self.log.borrow_mut().push(RegisterOp::Read(addr as usize));
+            self.value(addr as usize)
+        }
+
+        unsafe fn write(&self, addr: *mut u8, value: u8) {
+            let addr = addr as usize;
+            self.update(addr, RegisterOp::Write(addr, value), |_| value);
+        }
+
+        unsafe fn set_bit(&self, addr: *mut u8, bit: u8) {
+            let addr = addr as usize;
+            self.update(addr, RegisterOp::SetBit(addr, bit), |old| old | (1 << bit));
+        }
+
+        unsafe fn clear_bit(&self, addr: *mut u8, bit: u8) {
+            let addr = addr as usize;
+            self.update(addr, RegisterOp::ClearBit(addr, bit), |old| old & !(1 << bit));
+        }
+    }
+}
+
+//
+// 호스트 시뮬레이션 테스트 (`cargo test-host`)
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::sim;
+
+    #[test]
+    fn bit_operations_leave_other_bits_alone() {
+        let _guard = sim::lock();
+        sim::reset();
+
+        // 다른 코드(ISR 등)가 이미 켜 둔 비트
+        sim::set_register(0x10B, 0b1000_0001);
+        PORTL.set_pin_high(3);
+        assert_eq!(sim::register(0x10B), 0b1000_1001);
+        PORTL.set_pin_low(7);
+        assert_eq!(sim::register(0x10B), 0b0000_1001);
+
+        sim::set_register(0x24, 0b0100_0000);
+        PORTB.set_pin_output(1);
+        PORTB.set_pin_input(6);
+        assert_eq!(sim::register(0x24), 0b0000_0010);
+    }
+
+    #[test]
+    fn toggle_writes_pinx() {
+        let _guard = sim::lock();
+        sim::reset();
+
+        sim::set_register(0x102, 0b0000_0110);
+        PORTH.toggle_pin(1);
+        assert_eq!(sim::register(0x102), 0b0000_0100);
+        PORTH.toggle_pin(0);
+        assert_eq!(sim::register(0x102), 0b0000_0101);
+        // PINx 자체는 입력값 그대로
+        assert_eq!(sim::register(0x100), 0);
+    }
+
+    #[test]
+    fn whole_port_and_masked_access() {
+        let _guard = sim::lock();
+        sim::reset();
+
+        PORTA.set_output_mask(0xFF);
+        assert_eq!(sim::register(0x21), 0xFF);
+        PORTA.write(0xA5);
+        assert_eq!(PORTA.read_output(), 0xA5);
+
+        // 하위 니블만 교체, mask 밖의 value 비트는 무시
+        PORTA.modify(0x0F, 0xF3);
+        assert_eq!(sim::register(0x22), 0xA3);
+        PORTA.toggle_mask(0xF0);
+        assert_eq!(sim::register(0x22), 0x53);
+
+        PORTC.set_output_mask(0b0000_1111);
+        PORTC.set_input_mask(0b0000_0011);
+        assert_eq!(sim::register(0x27), 0b0000_1100);
+
+        sim::set_register(0x26, 0x3C);
+        assert_eq!(PORTC.read(), 0x3C);
+    }
+
+    #[test]
+    fn port_methods_go_through_register_access() {
+        use mock::{RecordingRegisters, RegisterOp::*};
+        let _guard = sim::lock();
+        sim::reset();
+
+        let regs = RecordingRegisters::new();
+        let portk = PORTK.with_registers(&regs);
+        portk.set_pin_output(2);
+        portk.toggle_pin(2);
+        regs.set_value(0x108, 0x0F);
+        portk.modify(0xF0, 0x50);
+        regs.set_value(0x106, 1 << 4);
+        assert!(portk.read_pin(4));
+
+        assert_eq!(
+            regs.take_log(),
+            [SetBit(0x107, 2), Write(0x106, 1 << 2), Read(0x108), Write(0x108, 0x5F), Read(0x106)]
+        );
+        // 가상 레지스터 파일(hal::sim)은 건드리지 않음
+        assert_eq!(sim::register(0x107), 0);
+    }
+}
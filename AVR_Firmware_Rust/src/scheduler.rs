@@ -1,42 +1,655 @@
+
+#![cfg_attr(not(test), allow(dead_code))]
+
 //
-// 필요한 크레이트
+// 필요한 크레이트 (레지스터/인터럽트는 hal 백엔드를 통해 접근)
 //
-use avr_device::atmega2560;
-use avr_device::interrupt;
+use crate::hal;
+use crate::hal::interrupt;
 
 //
 // RefCell, Mutex를 사용하기 위한 준비
 //
 use core::cell::RefCell;
-use avr_device::interrupt::Mutex;
+use crate::hal::interrupt::{CriticalSection, Mutex};
+use crate::trace;
+
+//
+// 타임베이스: tick 타이머/주기는 `tick` 모듈의 빌드 시 상수로 결정
+//
+use crate::tick::{TickTimer, TICK, TICK_US};
+
+//
+// 태스크 테이블 크기 (빌드 시 설정)
+//
+// 태스크 하나가 약 60바이트의 RAM을 차지하므로, 작은 빌드는 줄이고
+// 태스크가 많은 애플리케이션은 늘려서 사용합니다.
+// Cargo 기능으로 선택합니다: 기본 10, `max-tasks-4` / `max-tasks-8` / `max-tasks-16` / `max-tasks-32`
+//
+pub const MAX_TASKS: usize = {
+    const SIZES: [(bool, usize); 4] = [
+        (cfg!(feature = "max-tasks-4"), 4),
+        (cfg!(feature = "max-tasks-8"), 8),
+        (cfg!(feature = "max-tasks-16"), 16),
+        (cfg!(feature = "max-tasks-32"), 32),
+    ];
+    let mut max_tasks = 10;
+    let mut selected = 0;
+    let mut i = 0;
+    while i < SIZES.len() {
+        if SIZES[i].0 {
+            max_tasks = SIZES[i].1;
+            selected += 1;
+        }
+        i += 1;
+    }
+    assert!(selected <= 1, "select only one max-tasks feature");
+    max_tasks
+};
+
+const _: () = assert!(
+    MAX_TASKS >= 1 && MAX_TASKS <= u8::MAX as usize,
+    "MAX_TASKS must be 1..=255 (TaskHandle stores the slot index as u8)"
+);
+
+/// `now`가 `deadline`에 도달했는지 wrap-safe하게 비교합니다.
+///
+/// 두 시각의 차이를 부호 있는 값으로 해석하므로, 차이가 2^31ms(약 24일) 이내라면
+/// u32 카운터가 한 바퀴 돌아도(약 49일) 올바르게 판단합니다.
+#[inline(always)]
+pub const fn time_reached(now: u32, deadline: u32) -> bool {
+    (now.wrapping_sub(deadline) as i32) >= 0
+}
+
+//
+// 컨텍스트를 가진 태스크
+//
+
+/// 상태(컨텍스트)를 직접 들고 있는 태스크가 구현하는 트레이트
+///
+/// 같은 구조체를 여러 개 만들어 각각 등록하면, 예를 들어 LED 핀마다
+/// 별도의 blink 태스크를 전역 변수 없이 만들 수 있습니다.
+pub trait Runnable {
+    fn run(&mut self);
+}
+
+/// `fn(&mut T)`와 컨텍스트 `T`를 묶어 `Runnable`로 만드는 래퍼
+pub struct ContextTask<T> {
+    pub func: fn(&mut T),
+    pub ctx: T,
+}
+
+impl<T> ContextTask<T> {
+    pub const fn new(func: fn(&mut T), ctx: T) -> Self {
+        Self { func, ctx }
+    }
+}
+
+impl<T> Runnable for ContextTask<T> {
+    fn run(&mut self) {
+        (self.func)(&mut self.ctx);
+    }
+}
+
+/// 태스크 테이블에 저장되는 실행 대상 (일반 함수 또는 `Runnable` 객체)
+#[derive(Clone, Copy)]
+pub enum TaskFn {
+    Plain(fn()),
+    Runnable(RunnablePtr),
+}
+
+/// `&'static mut dyn Runnable`을 테이블에 복사 가능한 형태로 보관하기 위한 포인터
+///
+/// 원래의 `&'static mut` 참조는 `task_add_runnable()`로 넘겨진 뒤 스케줄러만
+/// 접근하며, `scheduler_run()`은 재진입하지 않으므로 동시에 두 곳에서
+/// `run()`이 호출되지 않습니다.
+#[derive(Clone, Copy)]
+pub struct RunnablePtr(*mut dyn Runnable);
+
+unsafe impl Send for RunnablePtr {}
+
+impl TaskFn {
+    fn call(self) {
+        match self {
+            TaskFn::Plain(f) => f(),
+            TaskFn::Runnable(RunnablePtr(ptr)) => unsafe { (*ptr).run() },
+        }
+    }
+}
 
 //
 // Task 구조체
 //
 #[derive(Clone, Copy)]
 pub struct Task {
-    pub task: fn(),
-    pub period: u16,
-    pub next_run: u16,
+    pub task: TaskFn,
+    pub period: u32,
+    pub next_run: u32,
     pub ready: bool,
+    pub suspended: bool,
+    /// 지금 실행 중 (`delay_yield()`/`wait_until()`으로 양보한 동안 다시 실행되지 않도록)
+    pub running: bool,
+    /// 우선순위 (값이 클수록 먼저 실행, 기본값 0)
+    pub priority: u8,
+    /// 태스크 종류 (주기/one-shot/이벤트)
+    pub kind: TaskKind,
+    /// `notify()`로 받은 알림 비트 (`take_notification()`으로 읽고 지움)
+    pub notify_bits: u16,
+    /// 실행 시간 통계
+    pub stats: TaskStats,
+    /// 아직 실행되지 않은 활성화 횟수 (`CatchUpPolicy::RunAll`에서 사용)
+    pub pending: u8,
+    /// 마지막 실행 이후 놓친 주기 수 (다음 실행 시 overrun 콜백으로 보고)
+    pub missed_since_run: u16,
+    /// 놓친 주기 처리 방식
+    pub catch_up: CatchUpPolicy,
+    /// 주기 누락/실행 시간 초과 시 호출되는 콜백
+    pub on_overrun: Option<OverrunHandler>,
+    /// 선언된 최악 실행 시간 (us, 0이면 미선언 → 스케줄 가능성 분석에서 제외)
+    pub wcet_us: u32,
+    /// 등록 시 부여되는 고유 번호 (슬롯 재사용 시 오래된 핸들을 구분하기 위함)
+    pub id: u16,
 }
 
 impl Task {
-    pub const fn new(task_fn: fn(), period: u16) -> Self {
+    pub const fn new(task_fn: TaskFn, period: u32, now: u32, id: u16) -> Self {
+        Self::new_phased(task_fn, period, now.wrapping_add(period), id)
+    }
+
+    /// 첫 실행 시각을 `first_run`으로 지정한 주기 태스크 (이후 `period`마다 실행)
+    pub const fn new_phased(task_fn: TaskFn, period: u32, first_run: u32, id: u16) -> Self {
         Self {
             task: task_fn,
             period,
-            next_run: period,
+            next_run: first_run,
             ready: period == 0,
+            suspended: false,
+            running: false,
+            priority: 0,
+            kind: TaskKind::Periodic,
+            notify_bits: 0,
+            stats: TaskStats::new(),
+            pending: 0,
+            missed_since_run: 0,
+            catch_up: CatchUpPolicy::RunOnce,
+            on_overrun: None,
+            wcet_us: 0,
+            id,
+        }
+    }
+
+    /// `deadline`에 한 번만 실행되는 태스크
+    pub const fn new_one_shot(task_fn: TaskFn, deadline: u32, now: u32, id: u16) -> Self {
+        Self {
+            task: task_fn,
+            period: deadline.wrapping_sub(now),
+            next_run: deadline,
+            ready: time_reached(now, deadline),
+            suspended: false,
+            running: false,
+            priority: 0,
+            kind: TaskKind::OneShot,
+            notify_bits: 0,
+            stats: TaskStats::new(),
+            pending: 0,
+            missed_since_run: 0,
+            catch_up: CatchUpPolicy::RunOnce,
+            on_overrun: None,
+            wcet_us: 0,
+            id,
+        }
+    }
+
+    /// `notify()`를 받았을 때만 실행되는 태스크
+    pub const fn new_event(task_fn: TaskFn, id: u16) -> Self {
+        Self {
+            task: task_fn,
+            period: 0,
+            next_run: 0,
+            ready: false,
+            suspended: false,
+            running: false,
+            priority: 0,
+            kind: TaskKind::Event,
+            notify_bits: 0,
+            stats: TaskStats::new(),
+            pending: 0,
+            missed_since_run: 0,
+            catch_up: CatchUpPolicy::RunOnce,
+            on_overrun: None,
+            wcet_us: 0,
+            id,
+        }
+    }
+
+    /// 주기를 기준으로 다음 실행 시각/ready 상태를 다시 계산
+    fn restart(&mut self, now: u32) {
+        self.next_run = now.wrapping_add(self.period);
+        self.ready = self.kind != TaskKind::Event && self.period == 0;
+        self.pending = 0;
+        self.missed_since_run = 0;
+    }
+
+    /// 주기 도달 시 (TIMER0_COMPA에서 호출) 활성화 처리
+    fn activate(&mut self, now: u32) {
+        if self.ready {
+            // 이전 활성화가 아직 실행되지 않은 채 다음 주기가 옴 → 주기 누락
+            self.missed_since_run = self.missed_since_run.saturating_add(1);
+            self.stats.missed = self.stats.missed.saturating_add(1);
+        }
+        self.ready = true;
+        self.pending = self.pending.saturating_add(1);
+
+        self.next_run = match self.catch_up {
+            // 실행이 늦어져도 원래 위상(next_run의 배수)을 유지
+            CatchUpPolicy::SkipMissed | CatchUpPolicy::RunAll => {
+                self.next_run.wrapping_add(self.period)
+            }
+            // 지금부터 한 주기 뒤 (위상이 밀릴 수 있음)
+            CatchUpPolicy::RunOnce => now.wrapping_add(self.period),
+        };
+    }
+
+    /// scheduler_run()이 태스크를 실행하기 직전 활성화 소비
+    fn consume(&mut self) {
+        if self.kind == TaskKind::Event {
+            self.ready = false;
+            return;
+        }
+        if self.period == 0 {
+            // period=0이면 매번 실행(ready 유지)
+            return;
+        }
+        match self.catch_up {
+            CatchUpPolicy::RunAll => {
+                self.pending = self.pending.saturating_sub(1);
+                self.ready = self.pending > 0;
+            }
+            CatchUpPolicy::SkipMissed | CatchUpPolicy::RunOnce => {
+                self.pending = 0;
+                self.ready = false;
+            }
+        }
+    }
+}
+
+/// 태스크 종류
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TaskKind {
+    /// `period`마다 실행 (0이면 `scheduler_run()`마다 실행)
+    Periodic,
+    /// deadline에 한 번 실행 후 슬롯 해제 (`call_after`/`call_at`)
+    OneShot,
+    /// `notify()`를 받을 때만 실행 (`task_add_event`)
+    Event,
+}
+
+/// 실행이 밀려 주기를 놓쳤을 때의 처리 방식
+///
+/// 예) 5ms 주기 태스크가 0ms에 시작해 12ms 동안 실행 (5ms, 10ms 활성화가 밀림)
+/// - `SkipMissed`: 12ms 실행 없음 → 15ms, 20ms, ...
+/// - `RunOnce`:    12ms에 한 번 → 15ms, 20ms, ...
+/// - `RunAll`:     12ms에 두 번(5ms, 10ms 몫) → 15ms, 20ms, ...
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CatchUpPolicy {
+    /// 주기를 놓쳤으면 밀린 실행을 버리고 다음 주기 위상(경계)까지 기다림
+    /// (늦은 실행보다 건너뛰는 편이 나은 제어 루프용, 버린 실행도 `MissedPeriod`로 보고)
+    SkipMissed,
+    /// 한 번만 실행, 다음 실행은 활성화 시점부터 한 주기 뒤 (기본값, 기존 동작)
+    RunOnce,
+    /// 놓친 활성화 횟수만큼 `scheduler_run()`마다 한 번씩 모두 실행 (최대 255회)
+    RunAll,
+}
+
+/// overrun 콜백으로 전달되는 타이밍 위반 종류
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimingViolation {
+    /// 마지막 실행 이후 `count`개의 주기를 놓침
+    MissedPeriod { count: u16 },
+    /// 한 번의 실행이 주기보다 오래 걸림
+    Overrun { elapsed_us: u32 },
+}
+
+/// 타이밍 위반 시 호출되는 콜백 (`scheduler_run()` 문맥에서 호출)
+pub type OverrunHandler = fn(TaskHandle, TimingViolation);
+
+//
+// 실행 시간 통계
+//
+// 측정은 `micros()`(tick 타이머 카운터 기반)로 하므로 사이클 단위로 정확하지 않습니다.
+// 분해능은 tick 타이머의 카운트 1번(분주비만큼의 CPU 사이클)이며, 기본 설정
+// (Timer0, 분주 64)에서는 64 사이클 = 4us입니다. → `STATS_RESOLUTION_US`
+// 분해능보다 짧게 실행된 태스크는 0us로 기록될 수 있습니다.
+//
+
+/// 실행 시간 통계의 분해능 (us, tick 타이머 카운트 1번에 해당하는 시간, 최소 1us)
+pub const STATS_RESOLUTION_US: u32 = if TICK.counts_to_us(1) > 0 { TICK.counts_to_us(1) } else { 1 };
+
+/// 태스크별 실행 시간 통계 (단위: us, 분해능 `STATS_RESOLUTION_US`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaskStats {
+    pub run_count: u32,
+    pub min_us: u32,
+    pub max_us: u32,
+    pub total_us: u64,
+    /// 놓친 주기 수
+    pub missed: u32,
+    /// 실행 시간이 주기를 넘긴 횟수
+    pub overruns: u32,
+}
+
+impl TaskStats {
+    pub const fn new() -> Self {
+        Self {
+            run_count: 0,
+            min_us: u32::MAX,
+            max_us: 0,
+            total_us: 0,
+            missed: 0,
+            overruns: 0,
+        }
+    }
+
+    /// 평균 실행 시간 (실행 기록이 없으면 0)
+    pub fn avg_us(&self) -> u32 {
+        if self.run_count == 0 {
+            0
+        } else {
+            (self.total_us / self.run_count as u64) as u32
+        }
+    }
+
+    fn record(&mut self, elapsed_us: u32) {
+        self.run_count = self.run_count.wrapping_add(1);
+        self.min_us = self.min_us.min(elapsed_us);
+        self.max_us = self.max_us.max(elapsed_us);
+        self.total_us = self.total_us.wrapping_add(elapsed_us as u64);
+    }
+}
+
+/// `stats()`가 돌려주는 스케줄러 전체 통계
+#[derive(Clone, Copy, Debug)]
+pub struct SchedulerStats {
+    /// 통계 측정 구간 길이 (ms, 마지막 `reset_stats()` 이후)
+    pub window_ms: u32,
+    /// 측정 구간 동안 태스크 실행에 쓰인 시간 (us)
+    pub busy_us: u64,
+    /// 슬롯별 태스크 통계 (빈 슬롯은 None)
+    pub tasks: [Option<TaskStats>; MAX_TASKS],
+}
+
+impl SchedulerStats {
+    /// CPU 사용률 (0.1% 단위, 0 ~ 1000)
+    pub fn cpu_load_permille(&self) -> u16 {
+        let window_us = self.window_ms as u64 * 1000;
+        if window_us == 0 {
+            return 0;
+        }
+        (self.busy_us * 1000 / window_us).min(1000) as u16
+    }
+
+    /// CPU 사용률 (%)
+    pub fn cpu_load_percent(&self) -> u8 {
+        (self.cpu_load_permille() / 10) as u8
+    }
+
+    /// 유휴 비율 (%)
+    pub fn idle_percent(&self) -> u8 {
+        100 - self.cpu_load_percent()
+    }
+}
+
+/// 스케줄러 API 오류
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SchedulerError {
+    /// 태스크 테이블에 빈 슬롯이 없음
+    TableFull,
+    /// 이미 제거되었거나 유효하지 않은 핸들
+    InvalidHandle,
+    /// 선언된 WCET로는 주기(deadline)를 지킬 수 없는 태스크 집합 (`AdmissionPolicy::Reject`)
+    Unschedulable,
+}
+
+/// 태스크의 현재 상태
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TaskState {
+    /// 실행 중 (`delay_yield()`/`wait_until()`으로 양보한 동안 포함)
+    Running,
+    /// 실행 대기 중 (다음 `scheduler_run()`에서 실행)
+    Ready,
+    /// 다음 주기를 기다리는 중
+    Waiting,
+    /// `suspend()`로 일시 정지됨
+    Suspended,
+}
+
+/// `task_add()`가 돌려주는 태스크 핸들
+///
+/// 슬롯 번호와 등록 id를 함께 보관하므로, 태스크가 제거된 뒤 같은 슬롯에
+/// 다른 태스크가 등록되더라도 이전 핸들은 `InvalidHandle`을 반환합니다.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TaskHandle {
+    index: u8,
+    id: u16,
+}
+
+impl TaskHandle {
+    /// 태스크 테이블의 슬롯 번호
+    pub fn slot(&self) -> u8 {
+        self.index
+    }
+
+    /// 등록 시 부여된 고유 번호
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// 핸들이 가리키는 태스크에 대해 임계구역 안에서 `f`를 실행
+    fn with_task<R>(&self, f: impl FnOnce(&mut Option<Task>, u32) -> R) -> Result<R, SchedulerError> {
+        interrupt::free(|cs| {
+            let now = *SYSTEM_TIME.borrow(cs).borrow();
+            let mut tasks = TASKS.borrow(cs).borrow_mut();
+            let slot = &mut tasks[self.index as usize];
+            if matches!(slot, Some(task) if task.id == self.id) {
+                Ok(f(slot, now))
+            } else {
+                Err(SchedulerError::InvalidHandle)
+            }
+        })
+    }
+
+    /// 태스크를 테이블에서 제거하고 슬롯을 비웁니다.
+    pub fn remove(self) -> Result<(), SchedulerError> {
+        self.with_task(|slot, _| *slot = None)
+    }
+
+    /// 태스크를 일시 정지합니다. (ready 플래그도 해제)
+    pub fn suspend(&self) -> Result<(), SchedulerError> {
+        self.with_task(|slot, _| {
+            if let Some(task) = slot.as_mut() {
+                task.suspended = true;
+                task.ready = false;
+                task.pending = 0;
+            }
+        })
+    }
+
+    /// 일시 정지된 태스크를 재개합니다. 다음 실행은 지금부터 한 주기 뒤입니다.
+    pub fn resume(&self) -> Result<(), SchedulerError> {
+        self.with_task(|slot, now| {
+            if let Some(task) = slot.as_mut()
+                && task.suspended
+            {
+                task.suspended = false;
+                task.restart(now);
+            }
+        })
+    }
+
+    /// 실행 주기를 변경합니다. 다음 실행은 지금부터 새 주기 뒤입니다.
+    ///
+    /// WCET를 선언한 태스크면 스케줄 가능성을 다시 분석합니다. (`set_wcet()`과 같은 처리)
+    pub fn set_period(&self, period: u32) -> Result<(), SchedulerError> {
+        self.update_timing(|task, now| {
+            task.period = period;
+            if !task.suspended {
+                task.restart(now);
+            }
+        })
+    }
+
+    /// 위상(phase)을 변경합니다. 다음 실행은 지금부터 `offset`ms 뒤이며,
+    /// 이후로는 그 시각을 기준으로 주기마다 실행됩니다.
+    pub fn set_phase(&self, offset: u32) -> Result<(), SchedulerError> {
+        self.with_task(|slot, now| {
+            if let Some(task) = slot.as_mut()
+                && task.kind == TaskKind::Periodic
+                && task.period > 0
+            {
+                task.restart(now);
+                task.next_run = now.wrapping_add(offset);
+            }
+        })
+    }
+
+    /// 현재 실행 주기(ms)
+    pub fn period(&self) -> Result<u32, SchedulerError> {
+        self.with_task(|slot, _| slot.as_ref().map_or(0, |task| task.period))
+    }
+
+    /// 태스크의 현재 상태
+    pub fn state(&self) -> Result<TaskState, SchedulerError> {
+        self.with_task(|slot, _| match slot.as_ref() {
+            Some(task) if task.running => TaskState::Running,
+            Some(task) if task.suspended => TaskState::Suspended,
+            Some(task) if task.ready => TaskState::Ready,
+            _ => TaskState::Waiting,
+        })
+    }
+
+    /// 우선순위를 변경합니다. (값이 클수록 먼저 실행)
+    ///
+    /// WCET를 선언한 태스크면 스케줄 가능성을 다시 분석합니다. (`set_wcet()`과 같은 처리)
+    pub fn set_priority(&self, priority: u8) -> Result<(), SchedulerError> {
+        self.update_timing(|task, _| task.priority = priority)
+    }
+
+    /// 현재 우선순위
+    pub fn priority(&self) -> Result<u8, SchedulerError> {
+        self.with_task(|slot, _| slot.as_ref().map_or(0, |task| task.priority))
+    }
+
+    /// 놓친 주기 처리 방식을 변경합니다.
+    pub fn set_catch_up(&self, policy: CatchUpPolicy) -> Result<(), SchedulerError> {
+        self.with_task(|slot, _| {
+            if let Some(task) = slot.as_mut() {
+                task.catch_up = policy;
+            }
+        })
+    }
+
+    /// 주기 누락/실행 시간 초과 시 호출할 콜백을 지정합니다. (`None`이면 해제)
+    pub fn set_overrun_handler(&self, handler: Option<OverrunHandler>) -> Result<(), SchedulerError> {
+        self.with_task(|slot, _| {
+            if let Some(task) = slot.as_mut() {
+                task.on_overrun = handler;
+            }
+        })
+    }
+
+    /// 태스크에 알림 비트를 보내고 즉시 ready로 만듭니다. (ISR에서 호출 가능)
+    ///
+    /// 주기 태스크라면 다음 주기를 기다리지 않고 한 번 더 실행되며,
+    /// 일시 정지된 태스크는 비트만 기록되고 실행되지 않습니다.
+    pub fn notify(&self, bits: u16) -> Result<(), SchedulerError> {
+        self.with_task(|slot, _| {
+            if let Some(task) = slot.as_mut() {
+                task.notify_bits |= bits;
+                if !task.suspended {
+                    task.ready = true;
+                }
+            }
+        })
+    }
+
+    /// 최악 실행 시간(us)을 선언합니다. (0이면 선언 해제)
+    ///
+    /// 선언하면 스케줄 가능성을 다시 분석하며, 주기를 지킬 수 없으면
+    /// `AdmissionPolicy`에 따라 `Unschedulable`을 반환하거나(값은 바뀌지 않음) 경고 콜백을 호출합니다.
+    pub fn set_wcet(&self, wcet_us: u32) -> Result<(), SchedulerError> {
+        self.update_timing(|task, _| task.wcet_us = wcet_us)
+    }
+
+    /// 타이밍 속성을 바꾼 태스크로 스케줄 가능성을 분석한 뒤 반영합니다.
+    ///
+    /// 분석과 반영을 한 임계구역에서 하므로, 그 사이 다른 등록/변경이 끼어들지 않습니다.
+    /// Reject 정책에서 불가능하면 태스크는 바뀌지 않습니다.
+    fn update_timing(&self, update: impl FnOnce(&mut Task, u32)) -> Result<(), SchedulerError> {
+        let report = interrupt::free(|cs| {
+            let now = *SYSTEM_TIME.borrow(cs).borrow();
+            let mut tasks = TASKS.borrow(cs).borrow_mut();
+            let index = self.index as usize;
+            let mut task = match tasks[index] {
+                Some(task) if task.id == self.id => task,
+                _ => return Err(SchedulerError::InvalidHandle),
+            };
+            update(&mut task, now);
+            let report = admit(cs, &tasks, index, &task)?;
+            tasks[index] = Some(task);
+            Ok(report)
+        })?;
+        if let Some(report) = report {
+            warn_unschedulable(*self, report);
         }
+        Ok(())
+    }
+
+    /// 선언된 최악 실행 시간 (us, 미선언이면 0)
+    pub fn wcet(&self) -> Result<u32, SchedulerError> {
+        self.with_task(|slot, _| slot.as_ref().map_or(0, |task| task.wcet_us))
+    }
+
+    /// 태스크의 실행 시간 통계
+    pub fn stats(&self) -> Result<TaskStats, SchedulerError> {
+        self.with_task(|slot, _| slot.as_ref().map_or(TaskStats::new(), |task| task.stats))
     }
 }
 
 //
 // 전역 데이터
 //
-static TASKS: Mutex<RefCell<[Option<Task>; 10]>> = Mutex::new(RefCell::new([None; 10]));
-static SYSTEM_TIME: Mutex<RefCell<u16>> = Mutex::new(RefCell::new(0));
+type TaskTable = [Option<Task>; MAX_TASKS];
+static TASKS: Mutex<RefCell<TaskTable>> = Mutex::new(RefCell::new([None; MAX_TASKS]));
+static SYSTEM_TIME: Mutex<RefCell<u32>> = Mutex::new(RefCell::new(0));
+static NEXT_TASK_ID: Mutex<RefCell<u16>> = Mutex::new(RefCell::new(0));
+static BATCH_PREEMPTION: Mutex<RefCell<bool>> = Mutex::new(RefCell::new(false));
+static AUTO_STAGGER: Mutex<RefCell<bool>> = Mutex::new(RefCell::new(false));
+static ADMISSION: Mutex<RefCell<AdmissionPolicy>> = Mutex::new(RefCell::new(AdmissionPolicy::Reject));
+/// `scheduler_run()`이 지금 실행 중인 태스크
+static CURRENT_TASK: Mutex<RefCell<Option<TaskHandle>>> = Mutex::new(RefCell::new(None));
+
+//
+// 유휴(idle) 처리: 실행할 태스크가 없을 때의 슬립 모드와 훅
+//
+static SLEEP_MODE: Mutex<RefCell<Option<SleepMode>>> = Mutex::new(RefCell::new(Some(SleepMode::Idle)));
+/// 유휴 진입 직전마다 호출되는 훅
+type IdleHook = fn();
+static IDLE_HOOK: Mutex<RefCell<Option<IdleHook>>> = Mutex::new(RefCell::new(None));
+/// tickless idle로 tick 타이머의 비교 주기를 늘린 구간 (None이면 평소 tick)
+static IDLE_STRETCH: Mutex<RefCell<Option<IdleStretch>>> = Mutex::new(RefCell::new(None));
+
+//
+// CPU 사용률 계산용 누적값 (측정 구간 시작 시각, 태스크 실행 누적 시간)
+//
+static STATS_SINCE: Mutex<RefCell<u32>> = Mutex::new(RefCell::new(0));
+static BUSY_US: Mutex<RefCell<u64>> = Mutex::new(RefCell::new(0));
+
+//
+// micros() 계산 시 카운터를 읽기 위해 tick 타이머 핸들을 보관
+//
+static TICK_TIMER: Mutex<RefCell<Option<TickTimer>>> = Mutex::new(RefCell::new(None));
+/// 1ms 미만으로 누적된 tick 시간 (us, TICK_US가 1000의 약수/배수가 아니어도 오차 없이 누적)
+static SUB_MS_US: Mutex<RefCell<u32>> = Mutex::new(RefCell::new(0));
 
 /// 전역 인터럽트 활성화 함수
 pub fn enable_interrupts() {
@@ -45,106 +658,1641 @@ pub fn enable_interrupts() {
     }
 }
 
-/// 타이머 초기화 (CTC 모드, 약 1ms 인터럽트)
+/// tick 타이머 초기화 (CTC 모드, `tick::TICK_US` 주기 인터럽트)
 ///
 /// - **변경점**: 이제 `timer_init()`이 직접 `Peripherals::take()`를 쓰지 않고
-///   호출 시점에 `tc0: atmega2560::TC0`을 인자로 받습니다.
-pub fn timer_init(tc0: atmega2560::TC0) {
-    // TCCR0A: CTC 모드 설정 (WGM0 = 2 → WGM01=1, WGM00=0)
-    tc0.tccr0a.write(|w| w.wgm0().bits(2));
-
-    // TCCR0B: 분주비 64 설정 (CS0 = 0b011)
-    tc0.tccr0b.write(|w| w.cs0().bits(0b011));
+///   호출 시점에 tick 타이머(`dp.TC0` / `dp.TC1` / `dp.TC2`)를 인자로 받습니다.
+/// - 넘긴 타이머가 `tick::TICK_SOURCE`와 다르면 panic합니다.
+pub fn timer_init(timer: impl Into<TickTimer>) {
+    let timer = timer.into();
+    assert!(timer.source() == TICK.source, "timer_init: timer does not match tick::TICK_SOURCE");
 
-    // OCR0A: 249 설정 → 1ms 주기
-    tc0.ocr0a.write(|w| unsafe { w.bits(249) });
+    timer.configure(&TICK);
 
-    // TIMSK0: 출력 비교 A 매치 인터럽트 활성화 (OCIE0A=1)
-    tc0.timsk0.write(|w| w.ocie0a().set_bit());
+    // micros()에서 사용할 수 있도록 전역에 보관
+    interrupt::free(|cs| {
+        *TICK_TIMER.borrow(cs).borrow_mut() = Some(timer);
+    });
 
     // 글로벌 인터럽트 활성화
     enable_interrupts();
 }
 
-#[avr_device::interrupt(atmega2560)]
-fn TIMER0_COMPA() {
-    interrupt::free(|cs| {
-        // 1) 시스템 시간 1ms 증가
-        let mut system_time_ref = SYSTEM_TIME.borrow(cs).borrow_mut();
-        *system_time_ref = system_time_ref.wrapping_add(1);
-        let now = *system_time_ref;
+/// tick 처리 (시스템 시간 증가, 태스크 ready 갱신, async 타이머 깨우기)
+///
+/// tick 타이머 인터럽트(`tick` 모듈)에서 `TICK_US`마다 호출됩니다.
+/// 태스크 주기는 ms 단위이므로, 2) 이후는 `millis()`가 바뀐 tick에서만 처리합니다.
+pub(crate) fn tick(cs: CriticalSection) {
+    // 1) 시스템 시간 증가 (tickless idle로 늘린 구간이 끝났으면 그 구간 전체)
+    let elapsed_us = match IDLE_STRETCH.borrow(cs).take() {
+        Some(stretch) => end_idle_stretch(cs, stretch, true),
+        None => TICK_US,
+    };
+    let (elapsed_ms, now) = add_time(cs, elapsed_us);
 
-        // 2) 등록된 태스크 확인 후 ready 플래그 세팅
+    // 시간을 먼저 갱신해야 ISR 진입 시각이 올바르게 기록됨
+    trace::builtin_isr_enter(trace::ISR_TICK);
+    if elapsed_ms > 0 {
+        tick_ms(cs, now);
+    }
+    trace::builtin_isr_exit(trace::ISR_TICK);
+}
+
+/// 시스템 시간에 `elapsed_us`를 더함 (1ms 미만은 SUB_MS_US에 누적). (늘어난 ms, 현재 ms)
+fn add_time(cs: CriticalSection, elapsed_us: u32) -> (u32, u32) {
+    let elapsed_ms = {
+        let mut sub_ms_ref = SUB_MS_US.borrow(cs).borrow_mut();
+        let total_us = *sub_ms_ref + elapsed_us;
+        *sub_ms_ref = total_us % 1000;
+        total_us / 1000
+    };
+    let mut system_time_ref = SYSTEM_TIME.borrow(cs).borrow_mut();
+    *system_time_ref = system_time_ref.wrapping_add(elapsed_ms);
+    (elapsed_ms, *system_time_ref)
+}
+
+/// ms가 바뀐 tick에서의 처리
+fn tick_ms(cs: CriticalSection, now: u32) {
+    // 2) 등록된 태스크 확인 후 ready 플래그 세팅
+    {
         let mut tasks_ref = TASKS.borrow(cs).borrow_mut();
         for slot in tasks_ref.iter_mut() {
             if let Some(task) = slot.as_mut() {
-                // period > 0 && now >= next_run → ready = true
-                if task.period > 0 && now >= task.next_run {
-                    task.ready = true;
-                    task.next_run = now.wrapping_add(task.period);
+                if task.suspended {
+                    continue;
+                }
+                match task.kind {
+                    TaskKind::OneShot => {
+                        // one-shot: deadline 도달 시 ready, 실행되면 scheduler_run()이 슬롯을 비움
+                        if time_reached(now, task.next_run) {
+                            task.ready = true;
+                        }
+                    }
+                    TaskKind::Periodic => {
+                        // period > 0 && now >= next_run (wrap-safe) → ready = true
+                        if task.period > 0 && time_reached(now, task.next_run) {
+                            task.activate(now);
+                        }
+                    }
+                    TaskKind::Event => {}
                 }
             }
         }
-    });
+    }
+
+    // 3) async executor의 Timer 깨우기
+    //    (waker가 executor 태스크를 notify하므로 위의 RefCell 대여를 모두 끝낸 뒤 호출)
+    crate::executor::on_tick(cs, now);
+
+    // 4) 태스크 watchdog 감시 (모든 태스크가 제때 check-in했을 때만 WDT 리셋)
+    crate::watchdog::on_tick(cs, now);
+}
+
+/// 태스크 등록
+///
+/// - `period`: 실행 주기(ms). 0이면 `scheduler_run()`마다 매번 실행
+/// - 빈 슬롯이 없으면 `SchedulerError::TableFull`
+/// - 자동 분산(`set_auto_stagger(true)`)이 켜져 있으면 첫 실행 시각을 자동으로 정합니다.
+pub fn task_add(task_fn: fn(), period: u32) -> Result<TaskHandle, SchedulerError> {
+    task_add_periodic(TaskFn::Plain(task_fn), period)
 }
 
-pub fn task_add(task_fn: fn(), period: u16) {
+/// 위상(offset)을 지정한 태스크 등록
+///
+/// 첫 실행은 지금부터 `offset`ms 뒤이고, 이후 `period`마다 실행됩니다.
+/// 예) 10ms, 1000ms 태스크를 각각 offset 1, 6으로 등록하면 같은 tick에 몰리지 않습니다.
+pub fn task_add_with_offset(task_fn: fn(), period: u32, offset: u32) -> Result<TaskHandle, SchedulerError> {
+    task_insert(|now, id| {
+        let first_run = if period == 0 { now } else { now.wrapping_add(offset) };
+        Task::new_phased(TaskFn::Plain(task_fn), period, first_run, id)
+    })
+}
+
+/// 컨텍스트를 가진 태스크 등록
+///
+/// `runnable`은 `'static` 수명이어야 하며, 등록 후에는 스케줄러가 소유한 것으로
+/// 간주합니다. (예: `avr_device::singleton!`으로 만든 참조)
+pub fn task_add_runnable(
+    runnable: &'static mut dyn Runnable,
+    period: u32,
+) -> Result<TaskHandle, SchedulerError> {
+    task_add_periodic(TaskFn::Runnable(RunnablePtr(runnable)), period)
+}
+
+/// 주기 태스크 공통 등록 (자동 분산이 켜져 있으면 위상을 골라 등록)
+fn task_add_periodic(task_fn: TaskFn, period: u32) -> Result<TaskHandle, SchedulerError> {
+    task_add_periodic_with_wcet(task_fn, period, 0).map(|(handle, _)| handle)
+}
+
+/// `task_add_periodic()` + WCET 선언 (0이면 분석하지 않음)
+fn task_add_periodic_with_wcet(
+    task_fn: TaskFn,
+    period: u32,
+    wcet_us: u32,
+) -> Result<(TaskHandle, Option<Schedulability>), SchedulerError> {
+    let auto_stagger = interrupt::free(|cs| *AUTO_STAGGER.borrow(cs).borrow());
+    if !auto_stagger || period < 2 {
+        return task_insert_admitted(|now, id| Task { wcet_us, ..Task::new(task_fn, period, now, id) });
+    }
+
+    // 계산(후보 수 × 태스크 수)은 임계구역 밖에서
+    let (base, phases) = release_phases();
+    let first_run = base.wrapping_add(stagger_offset(&phases, period));
+    task_insert_admitted(|now, id| {
+        // 계산하는 사이 첫 실행 시각이 지났다면 한 주기 미룸 (위상은 유지)
+        let first_run = if time_reached(now, first_run) {
+            first_run.wrapping_add(period)
+        } else {
+            first_run
+        };
+        Task { wcet_us, ..Task::new_phased(task_fn, period, first_run, id) }
+    })
+}
+
+/// 이벤트 태스크 등록: 주기 없이 `TaskHandle::notify()`를 받을 때만 실행됩니다.
+///
+/// ISR에서 `notify()`를 호출하면 폴링 없이 다음 `scheduler_run()`에서 바로 실행됩니다.
+pub fn task_add_event(task_fn: fn()) -> Result<TaskHandle, SchedulerError> {
+    task_insert(|_, id| Task::new_event(TaskFn::Plain(task_fn), id))
+}
+
+/// `ms` 후에 `task_fn`을 한 번만 실행합니다.
+///
+/// 실행되면 슬롯이 비워지며, 이후 핸들은 `InvalidHandle`을 반환합니다.
+/// 실행 전에 `remove()`하면 취소됩니다.
+pub fn call_after(ms: u32, task_fn: fn()) -> Result<TaskHandle, SchedulerError> {
+    task_insert(|now, id| Task::new_one_shot(TaskFn::Plain(task_fn), now.wrapping_add(ms), now, id))
+}
+
+/// 절대 시각 `deadline`(`millis()` 기준)에 `task_fn`을 한 번만 실행합니다.
+///
+/// 이미 지난 시각이면 다음 `scheduler_run()`에서 바로 실행됩니다.
+pub fn call_at(deadline: u32, task_fn: fn()) -> Result<TaskHandle, SchedulerError> {
+    task_insert(|now, id| Task::new_one_shot(TaskFn::Plain(task_fn), deadline, now, id))
+}
+
+fn task_insert(make_task: impl FnOnce(u32, u16) -> Task) -> Result<TaskHandle, SchedulerError> {
+    task_insert_admitted(make_task).map(|(handle, _)| handle)
+}
+
+/// 빈 슬롯에 태스크를 넣습니다. WCET를 선언한 태스크면 같은 임계구역에서
+/// 그 슬롯 기준으로 스케줄 가능성을 분석한 뒤 넣습니다. (`admit()`)
+fn task_insert_admitted(
+    make_task: impl FnOnce(u32, u16) -> Task,
+) -> Result<(TaskHandle, Option<Schedulability>), SchedulerError> {
     interrupt::free(|cs| {
+        let now = *SYSTEM_TIME.borrow(cs).borrow();
         let mut tasks = TASKS.borrow(cs).borrow_mut();
-        for slot in tasks.iter_mut() {
-            if slot.is_none() {
-                *slot = Some(Task::new(task_fn, period));
-                return;
-            }
-        }
-        // 빈 슬롯 없으면 등록 실패 처리 (여기서는 무시)
+        let index = tasks.iter().position(|slot| slot.is_none()).ok_or(SchedulerError::TableFull)?;
+        let mut next_id = NEXT_TASK_ID.borrow(cs).borrow_mut();
+        let id = *next_id;
+        let task = make_task(now, id);
+        let report = admit(cs, &tasks, index, &task)?;
+        *next_id = next_id.wrapping_add(1);
+        tasks[index] = Some(task);
+        Ok((TaskHandle { index: index as u8, id }, report))
+    })
+}
+
+//
+// 위상 자동 분산 (auto staggering)
+//
+// 주기 P인 새 태스크를 offset o(1..=P)에 두면, 주기 Pj / 다음 실행까지 rj인 기존 태스크와
+// 같은 tick에 실행되는 경우는 `o ≡ rj (mod gcd(P, Pj))`일 때뿐입니다.
+// 겹치는 태스크 수(= 새 태스크가 추가하는 tick당 최악 부하)가 가장 적은 offset을 고르고,
+// 같으면 겹치는 빈도(gcd / Pj의 합)가 적은 offset을 고릅니다.
+//
+// 후보는 앞쪽 `STAGGER_CANDIDATES`개 offset(1..=min(P, STAGGER_CANDIDATES))만 검사하므로
+// 계산량은 주기와 관계없이 후보 수 × 태스크 수로 고정됩니다.
+// - gcd가 후보 수 이하인 태스크와의 겹침은 창 안에서 모든 나머지를 확인하고,
+// - gcd가 더 큰 태스크는 창 안에서 후보를 많아야 하나씩만 막으므로 (후보 수 > MAX_TASKS)
+//   겹치지 않는 offset이 있으면 대부분 창 안에서 찾습니다. 첫 실행도 그만큼만 늦어집니다.
+//
+// `CatchUpPolicy::RunOnce`(기본값) 태스크는 실행이 밀리면 위상도 밀리므로,
+// 위상을 유지하려면 `SkipMissed`/`RunAll`을 함께 사용하세요.
+//
+
+/// 주기 태스크 등록 시 첫 실행 시각을 자동으로 분산할지 설정 (기본값 false)
+///
+/// 이미 등록된 태스크와 같은 tick에 몰리지 않도록 `task_add()`/`task_add_runnable()`의
+/// 첫 실행을 최대 한 주기(긴 주기는 `STAGGER_CANDIDATES`ms)까지 늦춥니다.
+/// (`task_add_with_offset()`은 지정한 offset 그대로)
+pub fn set_auto_stagger(enable: bool) {
+    interrupt::free(|cs| {
+        *AUTO_STAGGER.borrow(cs).borrow_mut() = enable;
     });
 }
 
-pub fn get_system_time() -> u16 {
+/// 현재 시각과, 주기 실행 중인 태스크별 (주기, 다음 실행까지 남은 ms)
+fn release_phases() -> (u32, [Option<(u32, u32)>; MAX_TASKS]) {
     interrupt::free(|cs| {
-        let time = *SYSTEM_TIME.borrow(cs).borrow();
-        time
+        let now = *SYSTEM_TIME.borrow(cs).borrow();
+        let mut phases = [None; MAX_TASKS];
+        for (phase, slot) in phases.iter_mut().zip(TASKS.borrow(cs).borrow().iter()) {
+            if let Some(task) = slot
+                && task.kind == TaskKind::Periodic
+                && task.period > 0
+                && !task.suspended
+            {
+                // 이미 지난 실행은 다음 tick에 실행되는 것으로 간주
+                let remaining = (task.next_run.wrapping_sub(now) as i32).max(1) as u32;
+                *phase = Some((task.period, remaining));
+            }
+        }
+        (now, phases)
     })
 }
 
-/// 스케줄러 실행 (ready 태스크를 찾아서 실제로 실행)
-pub fn scheduler_run() {
-    let mut ready_tasks: [Option<fn()>; 10] = [None; 10];
-    let mut count = 0;
+/// 자동 분산에서 검사하는 첫 실행 offset 후보 수 (1..=STAGGER_CANDIDATES ms)
+const STAGGER_CANDIDATES: u32 = 64;
 
-    // 1) 임계구역 내에서 ready 태스크만 복사해둠
-    interrupt::free(|cs| {
-        let mut tasks = TASKS.borrow(cs).borrow_mut();
-        for slot in tasks.iter_mut() {
-            if let Some(task) = slot.as_mut() {
-                if task.ready {
-                    ready_tasks[count] = Some(task.task);
-                    count += 1;
+const _: () = assert!(STAGGER_CANDIDATES as usize > MAX_TASKS, "stagger window must exceed the task table");
 
-                    // period > 0이면 한 번 실행 후 ready false
-                    // period=0이면 매번 실행(ready 유지)
-                    if task.period > 0 {
-                        task.ready = false;
-                    }
-                }
+/// tick당 최악 부하가 가장 작아지는 첫 실행 offset (1..=min(period, STAGGER_CANDIDATES))
+fn stagger_offset(phases: &[Option<(u32, u32)>], period: u32) -> u32 {
+    // (주기 gcd, 기존 태스크 위상 mod gcd, 겹침 빈도 가중치)
+    let mut constraints = [(1u32, 0u32, 0u32); MAX_TASKS];
+    let mut count = 0;
+    for &(other_period, remaining) in phases.iter().flatten() {
+        let g = gcd(period, other_period);
+        constraints[count] = (g, remaining % g, (g as u64 * 1024 / other_period as u64) as u32);
+        count += 1;
+    }
+    let constraints = &constraints[..count];
+
+    let mut best = (u32::MAX, u32::MAX, 1);
+    for offset in 1..=period.min(STAGGER_CANDIDATES) {
+        let (mut overlaps, mut weight) = (0u32, 0u32);
+        for &(g, phase, w) in constraints {
+            if offset % g == phase {
+                overlaps += 1;
+                weight += w;
             }
         }
-    });
-
-    // 2) 임계구역 밖에서 태스크 실제 실행
-    for i in 0..count {
-        if let Some(task_fn) = ready_tasks[i] {
-            task_fn();
+        if (overlaps, weight) < (best.0, best.1) {
+            best = (overlaps, weight, offset);
+            if overlaps == 0 {
+                break;
+            }
         }
     }
+    best.2
 }
 
-/// 블로킹 delay (ms 단위)
-pub fn delay(ms: u16) {
-    let start_time = get_system_time();
-    while get_system_time().wrapping_sub(start_time) < ms {
-        // 필요시 여기서 scheduler_run() 실행 가능
-        // scheduler_run();
+const fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+//
+// 스케줄 가능성 분석 (schedulability analysis)
+//
+// WCET(`task_add_with_wcet()` / `TaskHandle::set_wcet()`)를 선언한 주기 태스크만 분석합니다.
+// deadline은 주기와 같다고 보고, 두 단계로 검사합니다.
+//
+//   1) 사용률 U = Σ Ci / Ti 가 100%를 넘으면 불가능
+//      (참고용으로 Liu & Layland RM 한계 n(2^(1/n) - 1)도 함께 보고)
+//   2) 비선점 고정 우선순위 응답 시간 분석 (response-time analysis)
+//        w  = Bi + Σ_{j ∈ hp(i)} (⌊w / Tj⌋ + 1) · Cj   (수렴할 때까지 반복)
+//        Ri = w + Ci ≤ Ti
+//      - hp(i): 우선순위가 같거나 높은 다른 태스크 (같은 우선순위는 슬롯 순서라 보수적으로 포함)
+//      - Bi: 이미 시작한 낮은 우선순위 태스크에 의한 blocking. 태스크는 끝까지 실행되므로
+//        배치 선점(`set_batch_preemption(true)`)이면 그중 가장 긴 Ck, 아니면 배치 전체(Σ Ck)
+//      - period 0 / 이벤트 / one-shot 태스크는 주기가 없으므로 사용률과 자신의 deadline 검사에서 빠지고,
+//        낮은 우선순위면 blocking, 같거나 높은 우선순위면 한 번 실행되는 간섭(1 · Cj)으로 반영
+//
+// 등록과 `set_wcet()` / `set_period()` / `set_priority()`는 분석과 반영을 한 임계구역에서 하므로,
+// 분석하는 동안(태스크 수² × 반복 횟수) 인터럽트가 막힙니다. 가능하면 초기화 단계에서 호출하세요.
+// `resume()`은 다시 검사하지 않으므로, 재개한 뒤에는 `schedulability()`로 확인하세요.
+// ISR 실행 시간은 포함하지 않으므로 WCET에 여유를 두어 선언하세요.
+//
+
+/// 스케줄 가능성 분석 결과
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Schedulability {
+    /// 선언된 WCET 기준 CPU 사용률 (0.1% 단위)
+    pub utilization_permille: u16,
+    /// Liu & Layland RM 사용률 한계 (0.1% 단위, 분석 대상 태스크 수 기준)
+    pub rm_bound_permille: u16,
+    /// 최악 응답 시간이 주기를 넘는 첫 태스크의 슬롯 (None이면 스케줄 가능)
+    pub first_miss: Option<u8>,
+}
+
+impl Schedulability {
+    /// 모든 분석 대상 태스크가 주기 안에 끝나는지
+    pub fn is_schedulable(&self) -> bool {
+        self.utilization_permille <= 1000 && self.first_miss.is_none()
+    }
+
+    /// 사용률이 RM 한계 이하인지 (선점형 RM이라면 응답 시간 분석 없이도 충분한 조건)
+    pub fn within_rm_bound(&self) -> bool {
+        self.utilization_permille <= self.rm_bound_permille
+    }
+}
+
+/// 스케줄 불가능한 태스크 집합을 등록하려 할 때의 처리
+#[derive(Clone, Copy)]
+pub enum AdmissionPolicy {
+    /// 등록/WCET 선언을 거부하고 `SchedulerError::Unschedulable` 반환 (기본값)
+    Reject,
+    /// 그대로 등록하고 콜백으로 알림 (`scheduler_run()`이 아닌 등록한 문맥에서 호출)
+    Warn(fn(TaskHandle, Schedulability)),
+}
+
+/// 스케줄 불가능한 등록의 처리 방식 설정
+pub fn set_admission_policy(policy: AdmissionPolicy) {
+    interrupt::free(|cs| {
+        *ADMISSION.borrow(cs).borrow_mut() = policy;
+    });
+}
+
+/// WCET를 선언한 태스크 등록
+///
+/// `task_add()`와 같지만 등록 전에 스케줄 가능성을 분석합니다.
+/// - `wcet_us`: 한 번 실행의 최악 실행 시간(us)
+/// - 주기를 지킬 수 없으면 `AdmissionPolicy`에 따라 `Unschedulable` 또는 경고 콜백
+pub fn task_add_with_wcet(task_fn: fn(), period: u32, wcet_us: u32) -> Result<TaskHandle, SchedulerError> {
+    let (handle, report) = task_add_periodic_with_wcet(TaskFn::Plain(task_fn), period, wcet_us)?;
+    if let Some(report) = report {
+        warn_unschedulable(handle, report);
+    }
+    Ok(handle)
+}
+
+/// 현재 태스크 테이블의 스케줄 가능성 분석
+pub fn schedulability() -> Schedulability {
+    let (specs, batch_preemption) =
+        interrupt::free(|cs| (timing_specs(&TASKS.borrow(cs).borrow()), *BATCH_PREEMPTION.borrow(cs).borrow()));
+    analyze(&specs, batch_preemption)
+}
+
+/// 분석에 쓰는 태스크 타이밍 정보
+#[derive(Clone, Copy)]
+struct TimingSpec {
+    /// 주기 (ms)
+    period: u32,
+    wcet_us: u32,
+    priority: u8,
+    /// 주기적으로 활성화되는 태스크인지 (아니면 blocking 또는 한 번의 간섭으로만 반영)
+    periodic: bool,
+}
+
+impl TimingSpec {
+    fn of(task: &Task, wcet_us: u32) -> Self {
+        Self {
+            period: task.period,
+            wcet_us,
+            priority: task.priority,
+            periodic: task.kind == TaskKind::Periodic && task.period > 0,
+        }
+    }
+}
+
+/// 분석 대상 태스크(WCET 선언, 일시 정지 아님)의 타이밍 정보
+fn timing_spec(task: &Task) -> Option<TimingSpec> {
+    (task.wcet_us > 0 && !task.suspended).then(|| TimingSpec::of(task, task.wcet_us))
+}
+
+/// 태스크 테이블의 타이밍 정보
+fn timing_specs(tasks: &[Option<Task>; MAX_TASKS]) -> [Option<TimingSpec>; MAX_TASKS] {
+    tasks.map(|slot| slot.as_ref().and_then(timing_spec))
+}
+
+/// `tasks[slot]`을 `task`로 바꿨을 때의 분석 결과 (Reject 정책이면 불가능할 때 에러)
+///
+/// 분석 대상이 아니면 `None`. 호출한 임계구역 안에서 분석하므로 그동안 인터럽트가 막힙니다.
+fn admit(
+    cs: CriticalSection,
+    tasks: &[Option<Task>; MAX_TASKS],
+    slot: usize,
+    task: &Task,
+) -> Result<Option<Schedulability>, SchedulerError> {
+    let Some(spec) = timing_spec(task) else {
+        return Ok(None);
+    };
+    let mut specs = timing_specs(tasks);
+    specs[slot] = Some(spec);
+    let report = analyze(&specs, *BATCH_PREEMPTION.borrow(cs).borrow());
+    let reject = matches!(*ADMISSION.borrow(cs).borrow(), AdmissionPolicy::Reject);
+    if reject && !report.is_schedulable() {
+        return Err(SchedulerError::Unschedulable);
+    }
+    Ok(Some(report))
+}
+
+/// Warn 정책이면 스케줄 불가능한 등록을 콜백으로 알림
+fn warn_unschedulable(handle: TaskHandle, report: Schedulability) {
+    if report.is_schedulable() {
+        return;
+    }
+    let policy = interrupt::free(|cs| *ADMISSION.borrow(cs).borrow());
+    if let AdmissionPolicy::Warn(handler) = policy {
+        handler(handle, report);
+    }
+}
+
+/// 사용률 검사 + 비선점 응답 시간 분석
+fn analyze(specs: &[Option<TimingSpec>], batch_preemption: bool) -> Schedulability {
+    let mut utilization = 0u32;
+    let mut periodic_count = 0;
+    for spec in specs.iter().flatten().filter(|spec| spec.periodic) {
+        // Ci(us) / Ti(ms) = permille
+        utilization = utilization.saturating_add(spec.wcet_us.div_ceil(spec.period));
+        periodic_count += 1;
+    }
+
+    let mut first_miss = None;
+    for (index, task) in specs.iter().enumerate() {
+        let Some(task) = task.filter(|spec| spec.periodic) else {
+            continue;
+        };
+        if response_time_us(specs, index, batch_preemption) > task.period as u64 * 1000 {
+            first_miss = Some(index as u8);
+            break;
+        }
+    }
+
+    Schedulability {
+        utilization_permille: utilization.min(u16::MAX as u32) as u16,
+        rm_bound_permille: rm_bound_permille(periodic_count),
+        first_miss,
+    }
+}
+
+/// `specs[index]` 태스크의 최악 응답 시간 (us, deadline을 넘으면 그 시점에서 중단)
+fn response_time_us(specs: &[Option<TimingSpec>], index: usize, batch_preemption: bool) -> u64 {
+    let Some(task) = specs[index] else {
+        return 0;
+    };
+    let deadline_us = task.period as u64 * 1000;
+
+    // 낮은 우선순위 태스크에 의한 blocking
+    let lower = specs
+        .iter()
+        .enumerate()
+        .filter_map(|(j, spec)| spec.filter(|spec| j != index && spec.priority < task.priority));
+    let blocking = if batch_preemption {
+        lower.map(|spec| spec.wcet_us as u64).max().unwrap_or(0)
+    } else {
+        lower.map(|spec| spec.wcet_us as u64).sum()
+    };
+
+    // 시작 시각 w를 고정점 반복으로 계산 (높거나 같은 우선순위 태스크의 간섭)
+    let higher = |w: u64| -> u64 {
+        specs
+            .iter()
+            .enumerate()
+            .filter_map(|(j, spec)| spec.filter(|spec| j != index && spec.priority >= task.priority))
+            .map(|spec| {
+                // 주기 없는 태스크도 최소 한 번은 끼어들 수 있음
+                let releases = if spec.periodic { w / (spec.period as u64 * 1000) + 1 } else { 1 };
+                releases * spec.wcet_us as u64
+            })
+            .sum()
+    };
+    let mut start = blocking + higher(0);
+    loop {
+        let response = start + task.wcet_us as u64;
+        if response > deadline_us {
+            return response;
+        }
+        let next = blocking + higher(start);
+        if next == start {
+            return response;
+        }
+        start = next;
+    }
+}
+
+/// Liu & Layland 한계 n(2^(1/n) - 1) (0.1% 단위, 내림)
+fn rm_bound_permille(n: usize) -> u16 {
+    const BOUND: [u16; 10] = [1000, 828, 779, 756, 743, 734, 728, 724, 720, 717];
+    match n {
+        0 => 1000,
+        n if n <= BOUND.len() => BOUND[n - 1],
+        // n → ∞ 극한 ln 2
+        _ => 693,
+    }
+}
+
+/// 부팅 이후 경과 시간 (ms, 약 49.7일마다 wrap)
+pub fn millis() -> u32 {
+    interrupt::free(|cs| *SYSTEM_TIME.borrow(cs).borrow())
+}
+
+/// 부팅 이후 경과 시간 (us, 약 71.6분마다 wrap)
+///
+/// `SYSTEM_TIME`(ms)과 1ms 미만 누적값에 tick 타이머 카운트를 더해 계산합니다.
+/// 인터럽트가 막힌 사이 비교 매치가 발생해 아직 tick이 처리되지 않았다면
+/// OCFnA 플래그를 보고 tick 한 번(`TICK_US`)을 보정합니다.
+pub fn micros() -> u32 {
+    interrupt::free(|cs| {
+        let ms = *SYSTEM_TIME.borrow(cs).borrow();
+        let sub_ms_us = *SUB_MS_US.borrow(cs).borrow();
+        let timer_opt = TICK_TIMER.borrow(cs).borrow();
+        let timer = match *timer_opt {
+            Some(ref t) => t,
+            None => return ms.wrapping_mul(1000), // 타이머 초기화 전
+        };
+
+        // tickless idle 중이면 카운터가 idle 분주로 세고 있음 (슬립 중 ISR에서 호출된 경우)
+        let stretch = *IDLE_STRETCH.borrow(cs).borrow();
+        let (period_us, base_us, count_us) = match stretch {
+            Some(stretch) => (
+                stretch.counts * TICK.idle_count_us(),
+                stretch.start_phase_us,
+                TICK.idle_count_us(),
+            ),
+            None => (TICK_US, 0, TICK.counts_to_us(1)),
+        };
+
+        let mut count = timer.counter();
+        let mut pending_us = 0;
+        if timer.compare_pending() {
+            // 비교 매치 후 카운터가 0부터 다시 세고 있으므로 다시 읽음
+            count = timer.counter();
+            pending_us = period_us;
+        }
+
+        ms.wrapping_mul(1000)
+            .wrapping_add(sub_ms_us + base_us + pending_us)
+            .wrapping_add(if stretch.is_some() { count * count_us } else { TICK.counts_to_us(count) })
+    })
+}
+
+/// 이전 API 호환용 (= `millis()`)
+pub fn get_system_time() -> u32 {
+    millis()
+}
+
+/// 배치 선점 사용 여부 설정
+///
+/// 활성화하면 `scheduler_run()`이 배치를 실행하는 도중, 배치에 없던 더 높은
+/// 우선순위의 태스크가 ready가 될 경우 남은 태스크를 다음 `scheduler_run()`으로
+/// 미룹니다. (남은 태스크의 ready 상태는 유지되므로 실행이 누락되지는 않음)
+pub fn set_batch_preemption(enable: bool) {
+    interrupt::free(|cs| {
+        *BATCH_PREEMPTION.borrow(cs).borrow_mut() = enable;
+    });
+}
+
+/// 유휴 시 진입할 AVR 슬립 모드 (SMCR.SM)
+///
+/// 지금은 `Idle`만 지원합니다. Power-save/Power-down/Standby는 clkI/O를 멈추므로
+/// Timer0/1과 동기 모드 Timer2(`tick-timer2`)가 모두 멈춰 tick으로 깨어나지 못합니다.
+/// Power-save에서도 도는 Timer2는 비동기 모드(ASSR.AS2)뿐이고, 이 모드는 TOSC1/2에
+/// 32.768kHz 크리스털이 필요한데 Arduino Mega에는 없습니다.
+/// (크리스털을 단 보드라면 비동기 Timer2 tick 백엔드와 함께 `PowerSave`를 추가해야 함)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SleepMode {
+    /// CPU 클록만 정지. tick 타이머가 계속 동작하므로 시간이 정확히 유지됨
+    Idle,
+}
+
+/// 유휴 시 슬립 모드 설정 (`None`이면 슬립하지 않고 바로 반환, 기본값 `Idle`)
+pub fn set_sleep_mode(mode: Option<SleepMode>) {
+    interrupt::free(|cs| {
+        *SLEEP_MODE.borrow(cs).borrow_mut() = mode;
+    });
+}
+
+/// 유휴 진입 직전마다 호출되는 훅 설정 (예: LED 끄기, 주변장치 전원 차단)
+pub fn set_idle_hook(hook: Option<fn()>) {
+    interrupt::free(|cs| {
+        *IDLE_HOOK.borrow(cs).borrow_mut() = hook;
+    });
+}
+
+/// 실행할 수 있는 태스크가 있는지 (양보 중인 실행 중 태스크는 제외)
+fn any_task_ready(cs: CriticalSection) -> bool {
+    TASKS.borrow(cs).borrow().iter().flatten().any(|task| task.ready && !task.running)
+}
+
+/// 실행할 태스크가 없을 때 다음 deadline(또는 인터럽트로 태스크가 ready가 될 때)까지 슬립
+fn idle() {
+    let (mode, hook) = interrupt::free(|cs| {
+        (*SLEEP_MODE.borrow(cs).borrow(), *IDLE_HOOK.borrow(cs).borrow())
+    });
+    if let Some(hook) = hook {
+        hook();
+    }
+    let Some(mode) = mode else {
+        return;
+    };
+
+    while !sleep_until_interrupt(mode, Tickless::Until(None)) {}
+}
+
+//
+// tickless idle
+//
+// 유휴 슬립에 들어갈 때 다음 deadline(주기/one-shot 태스크의 `next_run`, async `Timer`,
+// `delay_yield()`의 종료 시각)까지 tick이 필요 없으면, tick 타이머를 idle 분주
+// (`TickConfig::idle_prescaler`, 16MHz에서 카운트당 64us)로 바꾸고 비교 주기를 그만큼 늘립니다.
+// - 늘린 비교 매치가 오면 tick ISR이, 다른 인터럽트(USART 등)로 일찍 깨어나면
+//   `sleep_until_interrupt()`가 지난 시간을 시스템 시간에 더하고 원래 tick으로 돌아갑니다.
+// - 마지막 tick 이후 흐른 시간(위상)을 카운터에 되돌려 넣어 tick/ms 경계가 밀리지 않습니다.
+//   (타이머를 다시 설정하는 동안의 몇 사이클만큼은 늦어질 수 있음)
+// - 한 번에 늘릴 수 있는 길이는 8비트 타이머(Timer0/2) 약 16ms, Timer1 약 4초이고,
+//   하드웨어 WDT를 쓰면 WDT 타임아웃의 절반으로 제한합니다.
+// - `preemptive` 기능에서는 스레드 문맥 전환에 매 tick이 필요하므로 늘리지 않습니다.
+//
+
+/// 늘린 비교 주기 정보
+#[derive(Clone, Copy)]
+struct IdleStretch {
+    /// 늘리기 직전 마지막 tick 이후 흐른 시간 (us)
+    start_phase_us: u32,
+    /// 늘린 비교 주기 (idle 분주 카운트 수)
+    counts: u32,
+}
+
+/// 이보다 짧게 쉴 때는 비교 주기를 늘리지 않음
+const MIN_STRETCH_US: u32 = 2 * TICK_US;
+
+/// 유휴 슬립에서 tick을 건너뛸지
+#[derive(Clone, Copy)]
+enum Tickless {
+    /// 매 tick 깨어남 (조건을 tick마다 다시 검사해야 하는 `wait_until()`)
+    Off,
+    /// 다음 deadline까지 건너뜀 (추가로 기다리는 시각이 있으면 함께 고려)
+    Until(Option<u32>),
+}
+
+/// ready 태스크가 없으면 다음 인터럽트까지 슬립 (ready 태스크가 있으면 슬립하지 않고 `true`)
+fn sleep_until_interrupt(mode: SleepMode, tickless: Tickless) -> bool {
+    // 검사와 슬립 사이에 인터럽트가 끼어들면 깨어날 기회를 놓치므로
+    // 인터럽트를 막은 상태에서 검사 후 `sei; sleep`으로 진입 (sei 다음 명령은 항상 실행됨)
+    interrupt::disable();
+    if interrupt::free(any_task_ready) {
+        unsafe { interrupt::enable() };
+        return true;
+    }
+    if let Tickless::Until(extra) = tickless {
+        interrupt::free(|cs| start_idle_stretch(cs, extra));
+    }
+    hal::sleep(mode);
+
+    // 늘린 비교 매치 전에 다른 인터럽트로 깨어났으면 지난 시간을 반영하고 원래 tick으로
+    interrupt::free(|cs| {
+        if let Some(stretch) = IDLE_STRETCH.borrow(cs).take() {
+            let elapsed_us = end_idle_stretch(cs, stretch, false);
+            let (elapsed_ms, now) = add_time(cs, elapsed_us);
+            if elapsed_ms > 0 {
+                tick_ms(cs, now);
+            }
+        }
+    });
+    false
+}
+
+/// 다음 deadline까지 tick이 필요 없으면 tick 타이머의 비교 주기를 늘림
+fn start_idle_stretch(cs: CriticalSection, extra: Option<u32>) {
+    if cfg!(feature = "preemptive") {
+        return;
+    }
+    let timer_ref = TICK_TIMER.borrow(cs).borrow();
+    let Some(timer) = timer_ref.as_ref() else {
+        return;
+    };
+    if timer.compare_pending() {
+        return;
+    }
+
+    let now = *SYSTEM_TIME.borrow(cs).borrow();
+    let phase_us = TICK.counts_to_us(timer.counter());
+    // 현재 ms 경계 이후 흐른 시간
+    let into_ms_us = *SUB_MS_US.borrow(cs).borrow() + phase_us;
+
+    let mut limit_us = TICK.max_counts * TICK.idle_count_us();
+    if let Some(max_ms) = crate::watchdog::max_idle_ms(cs) {
+        limit_us = limit_us.min(max_ms.saturating_mul(1000));
+    }
+    if let Some(deadline) = next_deadline(cs, now, extra) {
+        let ahead_ms = deadline.wrapping_sub(now);
+        if ahead_ms as i32 <= 0 {
+            return;
+        }
+        limit_us = limit_us.min(ahead_ms.saturating_mul(1000).saturating_sub(into_ms_us));
+    }
+    if limit_us < MIN_STRETCH_US {
+        return;
+    }
+
+    let counts = limit_us / TICK.idle_count_us();
+    timer.start_idle(&TICK, counts);
+    *IDLE_STRETCH.borrow(cs).borrow_mut() = Some(IdleStretch { start_phase_us: phase_us, counts });
+}
+
+/// 늘린 비교 주기를 끝내고 원래 tick으로 복귀
+///
+/// 지난 시간 중 tick 경계까지(TICK_US의 배수)를 반환하고, 나머지는 다음 tick까지의
+/// 위상으로 카운터에 넣습니다. `fired`면 늘린 비교 매치가 이미 발생한 것(tick ISR)입니다.
+fn end_idle_stretch(cs: CriticalSection, stretch: IdleStretch, fired: bool) -> u32 {
+    let timer_ref = TICK_TIMER.borrow(cs).borrow();
+    let Some(timer) = timer_ref.as_ref() else {
+        return 0;
+    };
+    let counts = if fired || timer.compare_pending() { stretch.counts } else { timer.counter() };
+    let total_us = stretch.start_phase_us + counts * TICK.idle_count_us();
+    let phase_us = total_us % TICK_US;
+    timer.resume(&TICK, TICK.us_to_counts(phase_us));
+    total_us - phase_us
+}
+
+/// `now` 이후 가장 가까운 deadline (주기/one-shot 태스크, async Timer, `extra`)
+fn next_deadline(cs: CriticalSection, now: u32, extra: Option<u32>) -> Option<u32> {
+    let tasks = TASKS.borrow(cs).borrow();
+    tasks
+        .iter()
+        .flatten()
+        .filter(|task| {
+            !task.suspended
+                && match task.kind {
+                    TaskKind::Periodic => task.period > 0,
+                    TaskKind::OneShot => true,
+                    TaskKind::Event => false,
+                }
+        })
+        .map(|task| task.next_run)
+        .chain(crate::executor::next_deadline(cs, now))
+        .chain(extra)
+        .min_by_key(|deadline| deadline.wrapping_sub(now))
+}
+
+//
+// scheduler_run()에서 사용하는 ready 목록 항목
+//
+#[derive(Clone, Copy)]
+struct ReadyEntry {
+    index: u8,
+    id: u16,
+    priority: u8,
+}
+
+/// ready 목록은 태스크 테이블의 모든 슬롯을 담을 수 있어야 함
+type ReadyList = [ReadyEntry; MAX_TASKS];
+
+enum BatchStep {
+    Run {
+        task_fn: TaskFn,
+        missed: u16,
+        handler: Option<OverrunHandler>,
+    },
+    /// `SkipMissed` 태스크의 밀린 실행을 버림
+    Dropped {
+        missed: u16,
+        handler: Option<OverrunHandler>,
+    },
+    Skip,
+    Preempted,
+}
+
+/// 우선순위 내림차순 정렬 (삽입 정렬: 같은 우선순위는 슬롯 순서를 유지)
+fn sort_by_priority(entries: &mut [ReadyEntry]) {
+    for i in 1..entries.len() {
+        let mut j = i;
+        while j > 0 && entries[j - 1].priority < entries[j].priority {
+            entries.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+/// 스케줄러 실행 (ready 태스크를 우선순위 순으로 실제로 실행)
+pub fn scheduler_run() {
+    let mut ready_tasks: ReadyList = [ReadyEntry { index: 0, id: 0, priority: 0 }; MAX_TASKS];
+    let mut count = 0;
+    // 이번 배치에 포함된 슬롯
+    let mut in_batch = [false; MAX_TASKS];
+    let mut preemption = false;
+
+    // 1) 임계구역 내에서 ready 태스크 목록만 작성
+    interrupt::free(|cs| {
+        preemption = *BATCH_PREEMPTION.borrow(cs).borrow();
+        let tasks = TASKS.borrow(cs).borrow();
+        for (index, slot) in tasks.iter().enumerate() {
+            if let Some(task) = slot.as_ref()
+                && task.ready
+                && !task.running
+            {
+                ready_tasks[count] = ReadyEntry {
+                    index: index as u8,
+                    id: task.id,
+                    priority: task.priority,
+                };
+                count += 1;
+                in_batch[index] = true;
+            }
+        }
+    });
+
+    // 실행할 태스크가 없으면 다음 deadline까지 슬립
+    if count == 0 {
+        idle();
+        return;
+    }
+
+    // 2) 높은 우선순위부터 실행되도록 정렬
+    sort_by_priority(&mut ready_tasks[..count]);
+
+    // 3) 태스크 하나씩 ready 플래그를 소비하고 임계구역 밖에서 실행
+    for entry in ready_tasks[..count].iter() {
+        let step = interrupt::free(|cs| {
+            let mut tasks = TASKS.borrow(cs).borrow_mut();
+
+            // 배치 밖에서 더 높은 우선순위 태스크가 ready가 되었으면 여기서 중단
+            if preemption {
+                let higher_ready = tasks.iter().enumerate().any(|(index, slot)| {
+                    !in_batch[index]
+                        && matches!(slot, Some(task) if task.ready && !task.running && task.priority > entry.priority)
+                });
+                if higher_ready {
+                    return BatchStep::Preempted;
+                }
+            }
+
+            // 목록 작성 이후 제거/일시정지된 태스크는 건너뜀
+            let slot = &mut tasks[entry.index as usize];
+            match slot.as_mut() {
+                Some(task) if task.id == entry.id && task.ready && !task.running => {
+                    if task.catch_up == CatchUpPolicy::SkipMissed
+                        && task.kind == TaskKind::Periodic
+                        && task.period > 0
+                        && task.missed_since_run > 0
+                    {
+                        // 주기를 놓친 활성화 → 실행하지 않고 다음 위상(`next_run`)까지 기다림
+                        let missed = core::mem::take(&mut task.missed_since_run);
+                        task.ready = false;
+                        task.pending = 0;
+                        return BatchStep::Dropped { missed, handler: task.on_overrun };
+                    }
+                    let step = BatchStep::Run {
+                        task_fn: task.task,
+                        missed: task.missed_since_run,
+                        handler: task.on_overrun,
+                    };
+                    task.missed_since_run = 0;
+                    task.running = true;
+                    if task.kind == TaskKind::OneShot {
+                        // one-shot은 실행과 동시에 슬롯 해제
+                        *slot = None;
+                    } else {
+                        task.consume();
+                    }
+                    step
+                }
+                _ => BatchStep::Skip,
+            }
+        });
+
+        match step {
+            BatchStep::Run { task_fn, missed, handler } => {
+                let handle = TaskHandle { index: entry.index, id: entry.id };
+                if missed > 0 && let Some(handler) = handler {
+                    handler(handle, TimingViolation::MissedPeriod { count: missed });
+                }
+
+                // 태스크 안에서 양보(`wait_until()`)하면 중첩 실행되므로 바깥 태스크를 복원
+                let outer = set_current_task(Some(handle));
+                trace::task_start(entry.index);
+                let yielded = yielded_us();
+                let start = micros();
+                task_fn.call();
+                // 양보하는 동안 다른 태스크/슬립에 쓴 시간은 제외
+                let elapsed = micros()
+                    .wrapping_sub(start)
+                    .wrapping_sub(yielded_us().wrapping_sub(yielded));
+                trace::task_end(entry.index);
+                set_current_task(outer);
+
+                if record_run(entry, elapsed) && let Some(handler) = handler {
+                    handler(handle, TimingViolation::Overrun { elapsed_us: elapsed });
+                }
+            }
+            BatchStep::Dropped { missed, handler } => {
+                if let Some(handler) = handler {
+                    let handle = TaskHandle { index: entry.index, id: entry.id };
+                    handler(handle, TimingViolation::MissedPeriod { count: missed });
+                }
+            }
+            BatchStep::Skip => {}
+            BatchStep::Preempted => break,
+        }
+    }
+}
+
+/// 현재 태스크를 바꾸고 이전 값을 반환
+fn set_current_task(handle: Option<TaskHandle>) -> Option<TaskHandle> {
+    interrupt::free(|cs| CURRENT_TASK.borrow(cs).replace(handle))
+}
+
+/// 지금 실행 중인 스케줄러 태스크의 핸들 (태스크 밖에서 호출하면 None)
+pub fn current_task() -> Option<TaskHandle> {
+    interrupt::free(|cs| *CURRENT_TASK.borrow(cs).borrow())
+}
+
+/// 현재 태스크가 받은 알림 비트를 읽고 지웁니다. (태스크 밖에서 호출하면 0)
+pub fn take_notification() -> u16 {
+    current_task().map_or(0, |handle| {
+        handle
+            .with_task(|slot, _| {
+                slot.as_mut()
+                    .map_or(0, |task| core::mem::take(&mut task.notify_bits))
+            })
+            .unwrap_or(0)
+    })
+}
+
+/// 실행 시간을 태스크 통계와 CPU 사용률 누적값에 반영
+///
+/// 실행 시간이 주기를 넘겼으면(overrun) `true`를 반환합니다.
+fn record_run(entry: &ReadyEntry, elapsed_us: u32) -> bool {
+    interrupt::free(|cs| {
+        let mut busy = BUSY_US.borrow(cs).borrow_mut();
+        *busy = busy.wrapping_add(elapsed_us as u64);
+
+        // one-shot처럼 실행 후 슬롯이 비워졌다면 태스크 통계는 남기지 않음
+        let mut tasks = TASKS.borrow(cs).borrow_mut();
+        match tasks[entry.index as usize].as_mut() {
+            Some(task) if task.id == entry.id => {
+                task.running = false;
+                task.stats.record(elapsed_us);
+                let overrun = task.period > 0 && elapsed_us as u64 > task.period as u64 * 1000;
+                if overrun {
+                    task.stats.overruns = task.stats.overruns.saturating_add(1);
+                }
+                overrun
+            }
+            _ => false,
+        }
+    })
+}
+
+/// 스케줄러 통계 스냅샷
+pub fn stats() -> SchedulerStats {
+    interrupt::free(|cs| {
+        let now = *SYSTEM_TIME.borrow(cs).borrow();
+        let since = *STATS_SINCE.borrow(cs).borrow();
+        let tasks = TASKS.borrow(cs).borrow();
+
+        let mut task_stats = [None; MAX_TASKS];
+        for (out, slot) in task_stats.iter_mut().zip(tasks.iter()) {
+            *out = slot.as_ref().map(|task| task.stats);
+        }
+
+        SchedulerStats {
+            window_ms: now.wrapping_sub(since),
+            busy_us: *BUSY_US.borrow(cs).borrow(),
+            tasks: task_stats,
+        }
+    })
+}
+
+/// 모든 통계를 초기화하고 새 측정 구간을 시작
+pub fn reset_stats() {
+    interrupt::free(|cs| {
+        *STATS_SINCE.borrow(cs).borrow_mut() = *SYSTEM_TIME.borrow(cs).borrow();
+        *BUSY_US.borrow(cs).borrow_mut() = 0;
+        for task in TASKS.borrow(cs).borrow_mut().iter_mut().flatten() {
+            task.stats = TaskStats::new();
+        }
+    });
+}
+
+/// 블로킹 delay (ms 단위)
+///
+/// 기다리는 동안 다른 태스크가 모두 멈추므로, 태스크 안에서는 `delay_yield()`를 사용하세요.
+pub fn delay(ms: u32) {
+    let deadline = millis().wrapping_add(ms);
+    while !time_reached(millis(), deadline) {}
+}
+
+//
+// 협조적 대기 (cooperative yield)
+//
+// 기다리는 동안 `scheduler_run()`을 중첩 호출해 다른 ready 태스크를 실행하고,
+// 실행할 태스크가 없으면 다음 인터럽트(tick 등)까지 슬립합니다.
+// - 양보한 태스크는 `running` 상태이므로 그 사이 다시 ready가 되어도 중첩 실행되지 않고,
+//   대기가 끝나 반환한 뒤의 `scheduler_run()`에서 실행됩니다.
+// - 중첩 실행된 태스크도 양보할 수 있으며, 먼저 양보한 태스크는 나중 태스크의 대기가
+//   끝날 때까지 돌아오지 못합니다. (스택 하나를 공유하므로 중첩 깊이만큼 스택 사용)
+// - 양보한 시간은 태스크 실행 시간 통계/overrun 판정에서 제외됩니다.
+//
+
+/// 양보 중 다른 태스크 실행과 슬립에 쓴 누적 시간 (us, wrap)
+static YIELDED_US: Mutex<RefCell<u32>> = Mutex::new(RefCell::new(0));
+
+fn yielded_us() -> u32 {
+    interrupt::free(|cs| *YIELDED_US.borrow(cs).borrow())
+}
+
+/// `ms` 동안 다른 태스크를 실행하며 대기
+pub fn delay_yield(ms: u32) {
+    let deadline = millis().wrapping_add(ms);
+    wait(|| time_reached(millis(), deadline), Tickless::Until(Some(deadline)));
+}
+
+/// `condition`이 참이 될 때까지 다른 태스크를 실행하며 대기
+///
+/// 조건은 태스크 실행 후와 인터럽트로 깨어날 때마다 다시 검사합니다.
+/// 조건을 바꾸는 것이 ISR뿐이라면 그 ISR이 슬립을 깨우므로 놓치지 않습니다.
+/// (`set_sleep_mode(None)`이면 슬립하지 않고 계속 검사)
+///
+/// 시간에 따라 바뀌는 조건일 수 있으므로 대기 중에는 tickless idle을 쓰지 않고 매 tick 깨어납니다.
+pub fn wait_until(condition: impl FnMut() -> bool) {
+    wait(condition, Tickless::Off);
+}
+
+fn wait(mut condition: impl FnMut() -> bool, tickless: Tickless) {
+    let yielded = yielded_us();
+    let start = micros();
+
+    while !condition() {
+        let mode = interrupt::free(|cs| *SLEEP_MODE.borrow(cs).borrow());
+        let ready = match mode {
+            Some(mode) => sleep_until_interrupt(mode, tickless),
+            None => interrupt::free(any_task_ready),
+        };
+        if ready {
+            scheduler_run();
+        }
+    }
+
+    // 안쪽 대기에서 이미 더한 시간은 빼고 이번 대기 전체를 누적
+    let nested = yielded_us().wrapping_sub(yielded);
+    let waited = micros().wrapping_sub(start).wrapping_sub(nested);
+    interrupt::free(|cs| {
+        let mut yielded = YIELDED_US.borrow(cs).borrow_mut();
+        *yielded = yielded.wrapping_add(waited);
+    });
+}
+
+//
+// 호스트 시뮬레이션 테스트 (`cargo test-host`)
+//
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::hal::{pac, sim};
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    /// 시뮬레이션 하드웨어와 스케줄러 전역 상태를 초기화하고 tick 타이머를 시작
+    pub(crate) fn setup() -> std::sync::MutexGuard<'static, ()> {
+        let guard = sim::lock();
+        sim::reset();
+        interrupt::free(|cs| {
+            *TASKS.borrow(cs).borrow_mut() = [None; MAX_TASKS];
+            *SYSTEM_TIME.borrow(cs).borrow_mut() = 0;
+            *SUB_MS_US.borrow(cs).borrow_mut() = 0;
+            *BATCH_PREEMPTION.borrow(cs).borrow_mut() = false;
+            *AUTO_STAGGER.borrow(cs).borrow_mut() = false;
+            *ADMISSION.borrow(cs).borrow_mut() = AdmissionPolicy::Reject;
+            *CURRENT_TASK.borrow(cs).borrow_mut() = None;
+            *SLEEP_MODE.borrow(cs).borrow_mut() = Some(SleepMode::Idle);
+            *IDLE_HOOK.borrow(cs).borrow_mut() = None;
+            *STATS_SINCE.borrow(cs).borrow_mut() = 0;
+            *BUSY_US.borrow(cs).borrow_mut() = 0;
+            *YIELDED_US.borrow(cs).borrow_mut() = 0;
+            *TICK_TIMER.borrow(cs).borrow_mut() = None;
+            *IDLE_STRETCH.borrow(cs).borrow_mut() = None;
+        });
+        crate::watchdog::tests::reset();
+        timer_init(pac::Peripherals::take().unwrap().TC0);
+        guard
+    }
+
+    /// `ms`까지 가상 시간을 진행하며 ready가 된 태스크를 실행 (그 시각에 ready가 된 태스크까지)
+    pub(crate) fn run_until(ms: u32) {
+        while millis() < ms || interrupt::free(any_task_ready) {
+            if interrupt::free(any_task_ready) {
+                scheduler_run();
+            } else {
+                sim::advance_us(TICK_US);
+            }
+        }
+    }
+
+    #[test]
+    fn periodic_task_runs_once_per_period() {
+        static RUNS: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        RUNS.store(0, Ordering::SeqCst);
+
+        task_add(|| { RUNS.fetch_add(1, Ordering::SeqCst); }, 10).unwrap();
+        run_until(100);
+
+        assert_eq!(RUNS.load(Ordering::SeqCst), 10);
+    }
+
+    #[test]
+    fn idle_sleeps_until_the_next_release() {
+        let _guard = setup();
+
+        task_add(|| {}, 10).unwrap();
+        scheduler_run();
+
+        // 156카운트(9984us)로 늘린 슬립 한 번 + 남은 16us는 평소 tick으로
+        assert_eq!(millis(), 10);
+        assert_eq!(micros(), 10_000);
+        assert_eq!(sim::sleep_count(), 2);
+    }
+
+    #[test]
+    fn long_idle_is_split_at_the_timer_limit() {
+        static RAN_AT: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+
+        task_add(|| RAN_AT.store(micros(), Ordering::SeqCst), 50).unwrap();
+        // tick 사이에서 슬립에 들어가도 tick 경계가 유지되는지
+        sim::advance_us(300);
+        scheduler_run();
+        assert_eq!(millis(), 50);
+        scheduler_run();
+
+        // Timer0는 한 번에 최대 256카운트(16.384ms) → 늘린 슬립 3번 + 남은 시간
+        assert_eq!(RAN_AT.load(Ordering::SeqCst), 50_000);
+        assert!(sim::sleep_count() <= 5, "{} sleeps", sim::sleep_count());
+    }
+
+    #[test]
+    fn delay_yield_sleeps_until_its_deadline() {
+        static WOKE_AT: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+
+        task_add_event(|| {
+            delay_yield(7);
+            WOKE_AT.store(micros(), Ordering::SeqCst);
+        })
+        .unwrap()
+        .notify(0)
+        .unwrap();
+        run_until(1);
+
+        assert_eq!(WOKE_AT.load(Ordering::SeqCst), 7_000);
+        assert!(sim::sleep_count() <= 2);
+    }
+
+    #[test]
+    fn offset_shifts_every_release() {
+        static RUNS: AtomicU32 = AtomicU32::new(0);
+        static FIRST: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        RUNS.store(0, Ordering::SeqCst);
+
+        task_add_with_offset(
+            || {
+                if RUNS.fetch_add(1, Ordering::SeqCst) == 0 {
+                    FIRST.store(millis(), Ordering::SeqCst);
+                }
+            },
+            10,
+            3,
+        )
+        .unwrap();
+        run_until(33);
+
+        assert_eq!(FIRST.load(Ordering::SeqCst), 3);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 4); // 3, 13, 23, 33
+    }
+
+    #[test]
+    fn one_shot_runs_once_and_frees_its_slot() {
+        static RUNS: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        RUNS.store(0, Ordering::SeqCst);
+
+        let handle = call_after(5, || { RUNS.fetch_add(1, Ordering::SeqCst); }).unwrap();
+        run_until(20);
+
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+        assert_eq!(handle.state(), Err(SchedulerError::InvalidHandle));
+    }
+
+    #[test]
+    fn event_task_runs_only_when_notified() {
+        static BITS: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        BITS.store(0, Ordering::SeqCst);
+
+        let handle = task_add_event(|| { BITS.fetch_or(take_notification() as u32, Ordering::SeqCst); }).unwrap();
+        run_until(10);
+        assert_eq!(BITS.load(Ordering::SeqCst), 0);
+
+        handle.notify(0b101).unwrap();
+        run_until(11);
+        assert_eq!(BITS.load(Ordering::SeqCst), 0b101);
+    }
+
+    #[test]
+    fn higher_priority_runs_first_in_a_batch() {
+        static FIRST: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        FIRST.store(0, Ordering::SeqCst);
+
+        task_add(|| { let _ = FIRST.compare_exchange(0, 1, Ordering::SeqCst, Ordering::SeqCst); }, 5).unwrap();
+        let high = task_add(|| { let _ = FIRST.compare_exchange(0, 2, Ordering::SeqCst, Ordering::SeqCst); }, 5).unwrap();
+        high.set_priority(3).unwrap();
+        run_until(5);
+
+        assert_eq!(FIRST.load(Ordering::SeqCst), 2);
+    }
+
+    /// 5ms 주기 태스크가 0ms 실행에서 12ms를 쓰게 하고 `until`ms까지의 실행 시각
+    fn catch_up_runs(policy: CatchUpPolicy, until: u32) -> std::vec::Vec<u32> {
+        static RUNS: std::sync::Mutex<std::vec::Vec<u32>> = std::sync::Mutex::new(std::vec::Vec::new());
+        RUNS.lock().unwrap().clear();
+
+        let handle = task_add_with_offset(
+            || {
+                let mut runs = RUNS.lock().unwrap();
+                runs.push(millis());
+                if runs.len() == 1 {
+                    drop(runs);
+                    sim::advance_us(12_000);
+                }
+            },
+            5,
+            5,
+        )
+        .unwrap();
+        handle.set_catch_up(policy).unwrap();
+        run_until(until);
+        RUNS.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn catch_up_policies_differ_after_an_overrun() {
+        {
+            let _guard = setup();
+            // 10ms, 15ms 활성화를 놓침 → 17ms 실행 없이 20ms 위상으로
+            assert_eq!(catch_up_runs(CatchUpPolicy::SkipMissed, 25), [5, 20, 25]);
+        }
+        {
+            let _guard = setup();
+            assert_eq!(catch_up_runs(CatchUpPolicy::RunOnce, 25), [5, 17, 20, 25]);
+        }
+        {
+            let _guard = setup();
+            assert_eq!(catch_up_runs(CatchUpPolicy::RunAll, 25), [5, 17, 17, 20, 25]);
+        }
+    }
+
+    #[test]
+    fn micros_includes_timer_count() {
+        let _guard = setup();
+
+        sim::advance_us(1500);
+
+        assert_eq!(millis(), 1);
+        assert_eq!(micros(), 1500);
+    }
+
+    #[test]
+    fn stats_have_timer_count_resolution() {
+        let _guard = setup();
+
+        // 302us 실행 → 기본 tick 설정(분주 64)에서는 시작/끝 시각이 4us 단위로 잘려
+        // 측정값은 실제 시간과 최대 한 분해능만큼 차이남
+        let handle = task_add(|| sim::advance_us(302), 10).unwrap();
+        run_until(20);
+
+        assert_eq!(STATS_RESOLUTION_US, 4);
+        let stats = handle.stats().unwrap();
+        assert_eq!(stats.run_count, 2);
+        // 0ms에 시작 → 0..302 = 300us, 10.302ms에 시작 → 10300..10604 = 304us
+        assert_eq!((stats.min_us, stats.max_us), (300, 304));
+    }
+
+    #[test]
+    fn scheduler_stats_report_cpu_load() {
+        let _guard = setup();
+
+        // 10ms마다 2.5ms 실행 (5, 15, ..., 95ms) → 100ms 동안 25%
+        let handle = task_add_with_offset(|| sim::advance_us(2500), 10, 5).unwrap();
+        run_until(100);
+
+        let report = stats();
+        assert_eq!((report.window_ms, report.busy_us), (100, 25_000));
+        assert_eq!(report.cpu_load_permille(), 250);
+        assert_eq!((report.cpu_load_percent(), report.idle_percent()), (25, 75));
+        let task = report.tasks[handle.slot() as usize].unwrap();
+        assert_eq!((task.run_count, task.avg_us()), (10, 2500));
+
+        reset_stats();
+        let report = stats();
+        assert_eq!((report.window_ms, report.busy_us), (0, 0));
+        assert_eq!(report.cpu_load_permille(), 0);
+        assert_eq!(handle.stats().unwrap().avg_us(), 0);
+    }
+
+    #[test]
+    fn runnable_tasks_keep_their_own_context() {
+        static TOTAL: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        TOTAL.store(0, Ordering::SeqCst);
+
+        // 같은 함수, 컨텍스트(증가량)만 다른 태스크 두 개
+        fn add(step: &mut u32) {
+            TOTAL.fetch_add(*step, Ordering::SeqCst);
+        }
+        let one = std::boxed::Box::leak(std::boxed::Box::new(ContextTask::new(add, 1)));
+        let hundred = std::boxed::Box::leak(std::boxed::Box::new(ContextTask::new(add, 100)));
+        let handle = task_add_runnable(one, 10).unwrap();
+        task_add_runnable(hundred, 10).unwrap();
+        run_until(50);
+
+        let runs = handle.stats().unwrap().run_count;
+        assert!(runs >= 5);
+        assert_eq!(TOTAL.load(Ordering::SeqCst), runs * 101);
+    }
+
+    #[test]
+    fn suspend_resume_phase_and_remove() {
+        static RUNS: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        RUNS.store(0, Ordering::SeqCst);
+
+        let handle = task_add_with_offset(|| { RUNS.fetch_add(1, Ordering::SeqCst); }, 10, 10).unwrap();
+        handle.suspend().unwrap();
+        assert_eq!(handle.state(), Ok(TaskState::Suspended));
+        run_until(30);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 0);
+
+        // 재개하면 지금(30ms)부터 한 주기 뒤
+        handle.resume().unwrap();
+        run_until(39);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 0);
+        run_until(40);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+
+        // 위상 변경: 지금(40ms)부터 3ms 뒤, 이후 주기마다
+        handle.set_phase(3).unwrap();
+        run_until(50);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 2);
+        run_until(53);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 3);
+
+        handle.remove().unwrap();
+        run_until(80);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 3);
+        assert_eq!(handle.state(), Err(SchedulerError::InvalidHandle));
+    }
+
+    #[test]
+    fn call_at_uses_an_absolute_time() {
+        static RAN_AT: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        RAN_AT.store(0, Ordering::SeqCst);
+
+        run_until(5);
+        call_at(12, || RAN_AT.store(millis(), Ordering::SeqCst)).unwrap();
+        run_until(20);
+        assert_eq!(RAN_AT.load(Ordering::SeqCst), 12);
+
+        // 이미 지난 시각이면 바로 실행
+        call_at(3, || RAN_AT.store(millis(), Ordering::SeqCst)).unwrap();
+        run_until(20);
+        assert_eq!(RAN_AT.load(Ordering::SeqCst), 20);
+    }
+
+    #[test]
+    fn overrun_handler_reports_the_elapsed_time() {
+        static VIOLATIONS: std::sync::Mutex<std::vec::Vec<TimingViolation>> =
+            std::sync::Mutex::new(std::vec::Vec::new());
+        let _guard = setup();
+        VIOLATIONS.lock().unwrap().clear();
+
+        let handle = task_add_with_offset(
+            || {
+                if millis() == 5 {
+                    sim::advance_us(12_000);
+                }
+            },
+            5,
+            5,
+        )
+        .unwrap();
+        handle
+            .set_overrun_handler(Some(|_, violation| VIOLATIONS.lock().unwrap().push(violation)))
+            .unwrap();
+        run_until(25);
+
+        let violations = VIOLATIONS.lock().unwrap();
+        assert_eq!(violations[0], TimingViolation::Overrun { elapsed_us: 12_000 });
+        assert!(violations[1..].iter().any(|v| matches!(v, TimingViolation::MissedPeriod { .. })));
+    }
+
+    #[test]
+    fn idle_hook_runs_and_sleep_can_be_disabled() {
+        static HOOK_CALLS: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        HOOK_CALLS.store(0, Ordering::SeqCst);
+
+        // 슬립하지 않으면 훅만 호출하고 바로 반환
+        set_idle_hook(Some(|| { HOOK_CALLS.fetch_add(1, Ordering::SeqCst); }));
+        set_sleep_mode(None);
+        scheduler_run();
+        assert_eq!(HOOK_CALLS.load(Ordering::SeqCst), 1);
+        assert_eq!((sim::sleep_count(), micros()), (0, 0));
+
+        set_idle_hook(None);
+        set_sleep_mode(Some(SleepMode::Idle));
+        task_add_with_offset(|| {}, 10, 3).unwrap();
+        scheduler_run();
+        assert_eq!(HOOK_CALLS.load(Ordering::SeqCst), 1);
+        assert!(sim::sleep_count() > 0);
+        assert_eq!(millis(), 3);
+    }
+
+    #[test]
+    fn zero_delay_does_not_block() {
+        let _guard = setup();
+
+        sim::advance_us(1500);
+        delay(0);
+        assert_eq!(get_system_time(), 1);
+    }
+
+    #[test]
+    fn stagger_offset_avoids_shared_ticks() {
+        // 1000ms 태스크가 1ms 뒤 실행 예정 → 10ms 태스크는 offset 2 (1 mod 10 회피)
+        assert_eq!(stagger_offset(&[Some((1000, 1))], 10), 2);
+        // 짝수/홀수 모두 겹치면 겹치는 빈도가 낮은 쪽(1000ms 태스크와만 겹치는 홀수)을 선택
+        assert_eq!(stagger_offset(&[Some((1000, 1)), Some((10, 2))], 2), 1);
+        assert_eq!(stagger_offset(&[], 7), 1);
+    }
+
+    #[test]
+    fn stagger_offset_search_is_bounded_for_long_periods() {
+        // 1분 주기라도 앞쪽 후보만 검사: 같은 주기 태스크가 1~3ms에 있으면 4ms
+        let taken = [Some((60_000, 1)), Some((60_000, 2)), Some((60_000, 3))];
+        assert_eq!(stagger_offset(&taken, 60_000), 4);
+        // 모든 offset이 2ms 태스크 하나와 겹치면 창 안의 첫 offset
+        assert_eq!(stagger_offset(&[Some((2, 1)), Some((2, 2))], 60_000), 1);
+        // gcd가 큰 태스크가 창 앞쪽을 모두 막아도 창 안에서 찾음
+        let mut phases = [None; MAX_TASKS];
+        for (i, phase) in phases.iter_mut().enumerate() {
+            *phase = Some((60_000, i as u32 + 1));
+        }
+        assert_eq!(stagger_offset(&phases, 60_000), MAX_TASKS as u32 + 1);
+    }
+
+    #[test]
+    fn over_utilized_task_set_is_rejected() {
+        let _guard = setup();
+
+        task_add_with_wcet(|| {}, 10, 6000).unwrap();
+        assert_eq!(task_add_with_wcet(|| {}, 20, 9000).err(), Some(SchedulerError::Unschedulable));
+        // 거부된 태스크는 등록되지 않음
+        assert_eq!(schedulability().utilization_permille, 600);
+    }
+
+    #[test]
+    fn long_lower_priority_task_blocks_a_short_period() {
+        let _guard = setup();
+
+        // 사용률은 낮지만 2ms 태스크가 1.8ms짜리 태스크 뒤에서 기다리면 주기를 넘김
+        let fast = task_add_with_wcet(|| {}, 2, 500).unwrap();
+        fast.set_priority(1).unwrap();
+        let slow = task_add(|| {}, 100).unwrap();
+        assert_eq!(slow.set_wcet(1800), Err(SchedulerError::Unschedulable));
+        assert_eq!(slow.wcet(), Ok(0));
+        assert_eq!(slow.set_wcet(1200), Ok(()));
+
+        let report = schedulability();
+        assert!(report.is_schedulable());
+        assert_eq!(report.utilization_permille, 262);
+        assert_eq!(report.rm_bound_permille, 828);
+        assert!(report.within_rm_bound());
+    }
+
+    #[test]
+    fn period_zero_wcet_task_only_blocks() {
+        let _guard = setup();
+
+        // 주기 없는 태스크는 사용률에 들어가지 않고 blocking으로만 반영 (0으로 나누지 않음)
+        let poll = task_add_with_wcet(|| {}, 0, 1500).unwrap();
+        assert_eq!(poll.wcet(), Ok(1500));
+        assert_eq!(schedulability().utilization_permille, 0);
+
+        // 2ms 태스크 + 1.5ms blocking = 2.1ms > 2ms
+        let fast = task_add(|| {}, 2).unwrap();
+        fast.set_priority(1).unwrap();
+        assert_eq!(fast.set_wcet(600), Err(SchedulerError::Unschedulable));
+        assert_eq!(fast.set_wcet(400), Ok(()));
+        assert!(schedulability().is_schedulable());
+    }
+
+    #[test]
+    fn higher_priority_one_shot_delays_a_lower_task() {
+        let _guard = setup();
+
+        // 1.5ms짜리 one-shot이 먼저 실행되면 2ms 태스크는 2.1ms에 끝남
+        let once = call_after(100, || {}).unwrap();
+        once.set_priority(2).unwrap();
+        once.set_wcet(1500).unwrap();
+        assert_eq!(task_add_with_wcet(|| {}, 2, 600).err(), Some(SchedulerError::Unschedulable));
+        assert!(task_add_with_wcet(|| {}, 2, 400).is_ok());
+    }
+
+    #[test]
+    fn period_and_priority_changes_are_rechecked() {
+        let _guard = setup();
+        set_batch_preemption(true);
+
+        // 배치 선점: 2ms 태스크의 blocking은 가장 긴 0.8ms → 1.4ms
+        let fast = task_add_with_wcet(|| {}, 2, 600).unwrap();
+        fast.set_priority(1).unwrap();
+        let first = task_add_with_wcet(|| {}, 100, 800).unwrap();
+        task_add_with_wcet(|| {}, 100, 800).unwrap();
+
+        // 1ms 주기면 1.4ms > 1ms → 거부되고 값은 그대로
+        assert_eq!(fast.set_period(1), Err(SchedulerError::Unschedulable));
+        assert_eq!(fast.period(), Ok(2));
+        // 하나를 더 높이면 간섭 0.8ms + blocking 0.8ms + 0.6ms = 2.2ms > 2ms
+        assert_eq!(first.set_priority(2), Err(SchedulerError::Unschedulable));
+        assert_eq!(first.priority(), Ok(0));
+        assert!(schedulability().is_schedulable());
+    }
+
+    #[test]
+    fn warn_policy_registers_and_reports() {
+        static WARNED: AtomicU32 = AtomicU32::new(u32::MAX);
+        let _guard = setup();
+        WARNED.store(u32::MAX, Ordering::SeqCst);
+
+        set_admission_policy(AdmissionPolicy::Warn(|handle, report| {
+            assert!(report.first_miss.is_some());
+            WARNED.store(handle.slot() as u32, Ordering::SeqCst);
+        }));
+        task_add_with_wcet(|| {}, 5, 3000).unwrap();
+        let handle = task_add_with_wcet(|| {}, 5, 3000).unwrap();
+
+        assert_eq!(WARNED.load(Ordering::SeqCst), handle.slot() as u32);
+        assert!(!schedulability().is_schedulable());
+    }
+
+    #[test]
+    fn delay_yield_runs_other_tasks() {
+        static FAST_RUNS: AtomicU32 = AtomicU32::new(0);
+        static DURING: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        FAST_RUNS.store(0, Ordering::SeqCst);
+
+        task_add(
+            || {
+                let before = FAST_RUNS.load(Ordering::SeqCst);
+                delay_yield(20);
+                DURING.store(FAST_RUNS.load(Ordering::SeqCst) - before, Ordering::SeqCst);
+            },
+            100,
+        )
+        .unwrap();
+        task_add(|| { FAST_RUNS.fetch_add(1, Ordering::SeqCst); }, 5).unwrap();
+        run_until(130);
+
+        // 100, 105, 110, 115ms 실행은 대기 중에, 120ms부터는 대기가 끝난 뒤에
+        assert_eq!(DURING.load(Ordering::SeqCst), 4);
+        assert_eq!(FAST_RUNS.load(Ordering::SeqCst), 26);
+    }
+
+    #[test]
+    fn yielding_task_is_not_reentered() {
+        static DEPTH: AtomicU32 = AtomicU32::new(0);
+        static MAX_DEPTH: AtomicU32 = AtomicU32::new(0);
+        static RUNS: AtomicU32 = AtomicU32::new(0);
+        let _guard = setup();
+        for counter in [&DEPTH, &MAX_DEPTH, &RUNS] {
+            counter.store(0, Ordering::SeqCst);
+        }
+
+        // 5ms 주기인데 첫 실행에서 12ms 양보 → 그 사이 다시 실행되지 않아야 함
+        let handle = task_add(
+            || {
+                let depth = DEPTH.fetch_add(1, Ordering::SeqCst) + 1;
+                MAX_DEPTH.fetch_max(depth, Ordering::SeqCst);
+                if RUNS.fetch_add(1, Ordering::SeqCst) == 0 {
+                    delay_yield(12);
+                    assert_eq!(current_task().map(|task| task.slot()), Some(0));
+                }
+                DEPTH.fetch_sub(1, Ordering::SeqCst);
+            },
+            5,
+        )
+        .unwrap();
+        task_add(|| {}, 1).unwrap();
+        run_until(30);
+
+        assert_eq!(MAX_DEPTH.load(Ordering::SeqCst), 1);
+        assert!(RUNS.load(Ordering::SeqCst) >= 4);
+        // 양보한 시간은 실행 시간에서 제외되어 overrun으로 보지 않음
+        let stats = handle.stats().unwrap();
+        assert!(stats.max_us < 1000);
+        assert_eq!(stats.overruns, 0);
     }
 }
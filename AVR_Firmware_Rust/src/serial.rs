@@ -1,18 +1,17 @@
-#![allow(dead_code)]
 
-use avr_device::atmega2560;
-use avr_device::interrupt::{self, Mutex};
+#![cfg_attr(not(test), allow(dead_code))]
+
+use crate::hal::{self, pac};
+use crate::hal::interrupt::{self, CriticalSection, Mutex};
 use core::cell::RefCell;
 
-//
-// CPU 클록 주파수 (16MHz 기준 예시)
-//
-const CPU_FREQUENCY: u32 = 16_000_000;
+use crate::tick::CPU_FREQUENCY;
+use crate::trace;
 
 //
 // 전역으로 USART0 핸들을 저장할 Mutex+RefCell
 //
-static USART0: Mutex<RefCell<Option<atmega2560::USART0>>> =
+static USART0: Mutex<RefCell<Option<pac::USART0>>> =
     Mutex::new(RefCell::new(None));
 
 //
@@ -28,30 +27,12 @@ static TX_TAIL: Mutex<RefCell<usize>> = Mutex::new(RefCell::new(0));
 ///
 /// - `usart0`: 메인에서 `dp.USART0`을 받았던 것을 그대로 인자로 전달
 /// - `baud_rate`: 예) 9600, 19200, 115200 등
-pub fn serial_init(usart0: atmega2560::USART0, baud_rate: u32) {
-    // 1) Double Speed 모드 활성화 (U2X0=1)
-    usart0.ucsr0a.modify(|_, w| w.u2x0().set_bit());
-
-    // 2) UBRR 계산: (F_CPU / (8 * baud)) - 1
+pub fn serial_init(usart0: pac::USART0, baud_rate: u32) {
+    // UBRR 계산 (Double Speed 모드): (F_CPU / (8 * baud)) - 1
     let ubrr = (CPU_FREQUENCY / (8 * baud_rate) - 1) as u16;
 
-    // 3) UBRR0 설정
-    usart0.ubrr0.write(|w| unsafe { w.bits(ubrr) });
-
-    // 4) UCSR0C: 비동기, 패리티 없음, 1 스톱비트, 데이터 8비트
-    usart0.ucsr0c.write(|w| {
-        w.umsel0().usart_async() // 비동기
-            .upm0().disabled()      // 패리티 없음
-            .usbs0().bit(false)     // 1 스톱비트
-            .ucsz0().chr8()         // 8비트
-    });
-
-    // 5) UCSR0B: RX/TX Enable
-    usart0.ucsr0b.write(|w| {
-        w.rxen0().set_bit()
-            .txen0().set_bit()
-            .udrie0().clear_bit() // 일단 비활성, 필요 시 인터럽트 enable
-    });
+    // 2배속, 비동기 8N1, RX/TX Enable
+    hal::usart_init(&usart0, ubrr);
 
     // 전역 USART0에 저장
     interrupt::free(|cs| {
@@ -62,12 +43,20 @@ pub fn serial_init(usart0: atmega2560::USART0, baud_rate: u32) {
 /// **비동기** 송신: 문자열을 링버퍼에 쌓고, UDRE0 인터럽트를 활성화하여
 /// 하드웨어가 준비될 때마다 1바이트씩 전송.
 pub fn write_str(s: &str) {
+    write_bytes(s.as_bytes());
+}
+
+/// 바이너리 데이터 **비동기** 송신. 링버퍼에 넣은 바이트 수를 반환합니다.
+///
+/// 버퍼가 부족하면 앞부분만 넣고 나머지는 버리므로, 모두 보내야 한다면
+/// 반환값을 보고 남은 부분을 나중에 다시 호출합니다. (`tx_space()` 참고)
+pub fn write_bytes(bytes: &[u8]) -> usize {
     interrupt::free(|cs| {
         // USART0 핸들 가져오기
         let usart0_opt = USART0.borrow(cs).borrow();
         let usart0 = match *usart0_opt {
             Some(ref u) => u,
-            None => return, // 초기화 안 됐다면 무시
+            None => return 0, // 초기화 안 됐다면 무시
         };
 
         // 링버퍼에 데이터 추가
@@ -75,7 +64,8 @@ pub fn write_str(s: &str) {
         let mut head = TX_HEAD.borrow(cs).borrow_mut();
         let tail = TX_TAIL.borrow(cs).borrow();
 
-        for &b in s.as_bytes() {
+        let mut written = 0;
+        for &b in bytes {
             // 다음 head 위치
             let next_head = (*head + 1) % TX_BUFFER_SIZE;
 
@@ -88,12 +78,30 @@ pub fn write_str(s: &str) {
             // 버퍼에 데이터 넣기
             tx_buffer[*head] = b;
             *head = next_head;
+            written += 1;
         }
 
         // UDRE0 인터럽트 활성화 (송신 시작)
         // (이미 인터럽트가 활성화되어 있더라도 문제없지만, 확실히 하기 위해 다시 set)
-        usart0.ucsr0b.modify(|_, w| w.udrie0().set_bit());
-    });
+        hal::usart_set_udre_interrupt(usart0, true);
+        written
+    })
+}
+
+/// `bytes`가 송신 링버퍼에 모두 들어갈 때만 한 번에 넣습니다. (다른 출력과 섞이지 않음)
+///
+/// 자리가 부족하면 아무것도 넣지 않고 `false`를 반환합니다.
+pub fn try_write_bytes(bytes: &[u8]) -> bool {
+    interrupt::free(|_| bytes.len() <= tx_space() && write_bytes(bytes) == bytes.len())
+}
+
+/// 송신 링버퍼의 남은 공간 (바이트)
+pub fn tx_space() -> usize {
+    interrupt::free(|cs| {
+        let head = *TX_HEAD.borrow(cs).borrow();
+        let tail = *TX_TAIL.borrow(cs).borrow();
+        (tail + TX_BUFFER_SIZE - head - 1) % TX_BUFFER_SIZE
+    })
 }
 
 /// 1바이트 **수신** (논블로킹) - 데이터 있으면 Some, 없으면 None
@@ -101,10 +109,8 @@ pub fn read_nonblocking() -> Option<u8> {
     let mut result = None;
     interrupt::free(|cs| {
         if let Some(ref usart0) = *USART0.borrow(cs).borrow() {
-            if usart0.ucsr0a.read().rxc0().bit_is_set() {
-                // 데이터 있으면 읽기
-                result = Some(usart0.udr0.read().bits());
-            }
+            // 데이터 있으면 읽기
+            result = hal::usart_read(usart0);
         }
     });
     result
@@ -124,30 +130,144 @@ pub fn serial_echo() {
 // UDRE0 인터럽트 핸들러
 //  - 하드웨어가 "UDR0 레지스터 비었다"고 알려주면, 링버퍼에서 다음 바이트를 꺼내 전송.
 //
+#[cfg(target_arch = "avr")]
 #[avr_device::interrupt(atmega2560)]
 fn USART0_UDRE() {
-    interrupt::free(|cs| {
-        let usart0_opt = USART0.borrow(cs).borrow();
-        let usart0 = match *usart0_opt {
-            Some(ref u) => u,
-            None => return,
-        };
+    interrupt::free(|cs| udre_isr(cs));
+}
 
-        let mut tx_buffer = TX_BUFFER.borrow(cs).borrow_mut();
-        let mut head = TX_HEAD.borrow(cs).borrow();
-        let mut tail = TX_TAIL.borrow(cs).borrow_mut();
-
-        // 버퍼 안에 보낼 데이터가 있으면 1바이트 전송
-        if *tail != *head {
-            // 다음 바이트 전송
-            let data = tx_buffer[*tail];
-            usart0.udr0.write(|w| unsafe { w.bits(data) });
-
-            // tail을 한 칸 이동
-            *tail = (*tail + 1) % TX_BUFFER_SIZE;
-        } else {
-            // 더 이상 보낼 데이터가 없으면 UDRE 인터럽트 비활성화
-            usart0.ucsr0b.modify(|_, w| w.udrie0().clear_bit());
+/// UDRE0 인터럽트 본문 (호스트 시뮬레이션에서는 `hal::sim`이 직접 호출)
+pub(crate) fn udre_isr(cs: CriticalSection) {
+    trace::builtin_isr_enter(trace::ISR_USART0_UDRE);
+    send_next_byte(cs);
+    trace::builtin_isr_exit(trace::ISR_USART0_UDRE);
+}
+
+fn send_next_byte(cs: CriticalSection) {
+    let usart0_opt = USART0.borrow(cs).borrow();
+    let usart0 = match *usart0_opt {
+        Some(ref u) => u,
+        None => return,
+    };
+
+    let tx_buffer = TX_BUFFER.borrow(cs).borrow();
+    let head = TX_HEAD.borrow(cs).borrow();
+    let mut tail = TX_TAIL.borrow(cs).borrow_mut();
+
+    // 버퍼 안에 보낼 데이터가 있으면 1바이트 전송
+    if *tail != *head {
+        // 다음 바이트 전송
+        let data = tx_buffer[*tail];
+        hal::usart_write(usart0, data);
+
+        // tail을 한 칸 이동
+        *tail = (*tail + 1) % TX_BUFFER_SIZE;
+    } else {
+        // 더 이상 보낼 데이터가 없으면 UDRE 인터럽트 비활성화
+        hal::usart_set_udre_interrupt(usart0, false);
+    }
+}
+
+//
+// 호스트 시뮬레이션 테스트 (`cargo test-host`)
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::sim;
+
+    fn setup() -> std::sync::MutexGuard<'static, ()> {
+        let guard = sim::lock();
+        sim::reset();
+        interrupt::free(|cs| {
+            *USART0.borrow(cs).borrow_mut() = None;
+            *TX_HEAD.borrow(cs).borrow_mut() = 0;
+            *TX_TAIL.borrow(cs).borrow_mut() = 0;
+        });
+        serial_init(pac::Peripherals::take().unwrap().USART0, 115200);
+        guard
+    }
+
+    fn output() -> std::vec::Vec<u8> {
+        let mut buf = [0u8; 1024];
+        let len = sim::serial_take_output(&mut buf);
+        buf[..len].to_vec()
+    }
+
+    #[test]
+    fn write_str_is_sent_in_order() {
+        let _guard = setup();
+
+        write_str("hello");
+        sim::serial_flush();
+
+        assert_eq!(output(), b"hello");
+    }
+
+    #[test]
+    fn bytes_leave_at_the_configured_baud_rate() {
+        let _guard = setup();
+
+        // UBRR0 = 16 → 117647 baud → 1바이트(10비트) 약 85us
+        write_str("abcd");
+        sim::advance_us(170);
+
+        assert_eq!(output(), b"ab");
+    }
+
+    #[test]
+    fn full_buffer_drops_the_overflow() {
+        let _guard = setup();
+
+        // 링버퍼는 한 칸을 비워 두므로 TX_BUFFER_SIZE - 1 바이트까지만 보관
+        let text = "0123456789".repeat(20);
+        write_str(&text);
+        sim::serial_flush();
+
+        assert_eq!(output(), &text.as_bytes()[..TX_BUFFER_SIZE - 1]);
+    }
+
+    #[test]
+    fn try_write_bytes_is_all_or_nothing() {
+        let _guard = setup();
+
+        assert_eq!(tx_space(), TX_BUFFER_SIZE - 1);
+        write_str(&"x".repeat(TX_BUFFER_SIZE - 4));
+        assert_eq!(tx_space(), 3);
+
+        assert!(!try_write_bytes(b"abcd"));
+        assert!(try_write_bytes(b"abc"));
+        sim::serial_flush();
+
+        assert!(output().ends_with(b"xabc"));
+        assert_eq!(tx_space(), TX_BUFFER_SIZE - 1);
+    }
+
+    #[test]
+    fn ring_buffer_wraps_around() {
+        let _guard = setup();
+
+        let mut expected = std::vec::Vec::new();
+        for round in 0..3u8 {
+            let text: std::string::String = (0..100).map(|i| (b'a' + (i + round) % 26) as char).collect();
+            write_str(&text);
+            sim::serial_flush();
+            expected.extend_from_slice(text.as_bytes());
         }
-    });
+
+        assert_eq!(output(), expected);
+    }
+
+    #[test]
+    fn echo_returns_received_bytes() {
+        let _guard = setup();
+
+        assert_eq!(read_nonblocking(), None);
+        sim::serial_inject(b"x");
+        serial_echo();
+        sim::serial_flush();
+
+        assert_eq!(output(), b"x");
+        assert_eq!(read_nonblocking(), None);
+    }
 }
@@ -0,0 +1,609 @@
+
+//
+// 태스크/ISR 간 공유 자원 중재: Mutex, 카운팅 Semaphore, TryLock
+//
+// - `Mutex<T>`: 데이터를 감싸는 상호 배제 락. guard가 drop되면 풀립니다.
+//     try_lock()          : 바로 시도 (ISR에서도 호출 가능)
+//     try_lock_or_wait()  : 실패하면 현재 태스크를 대기자로 등록 → 풀릴 때 `notify()`로 다시 실행
+//     lock()              : 풀릴 때까지 다른 태스크를 실행하며 대기 (`scheduler::wait_until`)
+// - `Semaphore`: 카운팅 세마포어. `release()`는 ISR에서도 호출할 수 있습니다.
+// - `TryLock<T>`: 임계구역 없이 플래그 하나로 동작하는 non-blocking 락
+//
+// 협력형 스케줄러에서는 락을 가진 태스크가 양보(`delay_yield()` 등)하는 동안 다른 태스크가
+// 그 위에 중첩 실행되므로, 그 태스크가 같은 락을 기다리면 영원히 풀리지 않습니다.
+// `lock()`은 이 경우 기다리지 않고 `SyncError::WouldDeadlock`을 반환합니다.
+//
+// `preemptive` 기능에서는 스레드가 `lock()`으로 기다릴 때 락을 가진 스레드에 자신의
+// 우선순위를 상속하고 양보하므로, 중간 우선순위 스레드가 끼어드는 우선순위 역전을 막습니다.
+// (락을 놓으면 아직 가진 다른 락으로 상속받은 우선순위 중 가장 높은 값, 없으면 원래 값으로 돌아감)
+//
+// 예) I2C 버스를 여러 태스크가 공유
+//   static I2C_BUS: sync::Mutex<I2cBus> = sync::Mutex::new(I2cBus::new());
+//   fn sensor_task() {
+//       let Some(mut bus) = I2C_BUS.try_lock_or_wait(BUS_FREE) else { return }; // 풀리면 다시 실행됨
+//       bus.read(...);
+//   }
+//
+#![cfg_attr(not(test), allow(dead_code))]
+
+use core::cell::{RefCell, UnsafeCell};
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::hal::interrupt;
+#[cfg(all(feature = "preemptive", target_arch = "avr"))]
+use crate::kernel::{self, ThreadId};
+use crate::scheduler::{self, TaskHandle, TaskState};
+
+/// 락/세마포어 하나에 등록할 수 있는 대기 태스크 수
+const MAX_WAITERS: usize = 4;
+
+/// 동기화 API 오류
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SyncError {
+    /// 락을 가진 태스크가 양보 중이거나(이 태스크 아래에 중첩됨) 자기 자신이라 풀릴 수 없음
+    WouldDeadlock,
+}
+
+//
+// 대기 태스크 목록: 자원이 풀리면 모두 알림을 받고 다시 시도
+//
+#[derive(Clone, Copy)]
+struct Waiters([Option<(TaskHandle, u16)>; MAX_WAITERS]);
+
+impl Waiters {
+    const fn new() -> Self {
+        Self([None; MAX_WAITERS])
+    }
+
+    /// 현재 태스크를 대기자로 등록. 태스크 밖이거나 목록이 가득 차면 `false`
+    fn add_current(&mut self, bits: u16) -> bool {
+        let Some(handle) = scheduler::current_task() else {
+            return false;
+        };
+        if self.0.iter().flatten().any(|&(waiter, _)| waiter == handle) {
+            return true;
+        }
+        match self.0.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some((handle, bits));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn take(&mut self) -> Self {
+        core::mem::replace(self, Self::new())
+    }
+
+    /// 임계구역 밖에서 호출 (notify가 태스크 테이블을 빌림)
+    fn notify_all(&self) {
+        for &(handle, bits) in self.0.iter().flatten() {
+            // 대기 태스크가 제거되었다면 알림만 생략
+            let _ = handle.notify(bits);
+        }
+    }
+}
+
+/// 대기자로 등록하지 못했으면 바로 다시 실행되도록 알림 (알림을 잃지 않도록 폴링으로 대체)
+fn notify_current_now(bits: u16) {
+    if let Some(handle) = scheduler::current_task() {
+        let _ = handle.notify(bits);
+    }
+}
+
+/// `ready`가 참이 될 때까지 대기
+///
+/// 스케줄러 문맥에서는 다른 태스크를 실행하며 기다리고,
+/// `preemptive` 기능의 스레드에서는 다른 스레드에 양보합니다.
+fn block_until(mut ready: impl FnMut() -> bool) {
+    #[cfg(all(feature = "preemptive", target_arch = "avr"))]
+    if kernel::current() != ThreadId(0) {
+        while !ready() {
+            kernel::yield_now();
+        }
+        return;
+    }
+    scheduler::wait_until(&mut ready);
+}
+
+//
+// Mutex
+//
+
+/// 락을 가진 쪽
+#[derive(Clone, Copy)]
+struct Owner {
+    /// 락을 잡은 스케줄러 태스크 (태스크 밖에서 잡았으면 None)
+    task: Option<TaskHandle>,
+    /// 락을 잡은 스레드
+    #[cfg(all(feature = "preemptive", target_arch = "avr"))]
+    thread: ThreadId,
+}
+
+impl Owner {
+    fn current() -> Self {
+        Self {
+            task: scheduler::current_task(),
+            #[cfg(all(feature = "preemptive", target_arch = "avr"))]
+            thread: kernel::current(),
+        }
+    }
+
+    /// 기다리는 동안 이 owner가 실행될 수 없는지
+    ///
+    /// 같은 스택에서 owner는 지금 태스크 아래에 있으므로(양보 중이거나 태스크 밖의 코드),
+    /// 지금 태스크가 돌아가기 전에는 락을 놓을 수 없습니다.
+    /// (async 태스크처럼 guard를 보관한 채 반환한 태스크는 `Running`이 아니므로 기다릴 수 있음)
+    fn blocked_by_caller(&self) -> bool {
+        #[cfg(all(feature = "preemptive", target_arch = "avr"))]
+        if self.thread != kernel::current() {
+            return false;
+        }
+        match self.task {
+            Some(task) => matches!(task.state(), Ok(TaskState::Running)),
+            None => true,
+        }
+    }
+}
+
+struct MutexState {
+    owner: Option<Owner>,
+    waiters: Waiters,
+    /// owner 스레드가 우선순위를 상속받았는지
+    boosted: bool,
+}
+
+/// 데이터를 감싸는 상호 배제 락
+///
+/// ```ignore
+/// static TX_LOCK: sync::Mutex<()> = sync::Mutex::new(());
+/// ```
+pub struct Mutex<T> {
+    state: interrupt::Mutex<RefCell<MutexState>>,
+    data: UnsafeCell<T>,
+}
+
+// data는 락을 잡은 guard를 통해서만 접근합니다.
+unsafe impl<T: Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            state: interrupt::Mutex::new(RefCell::new(MutexState {
+                owner: None,
+                waiters: Waiters::new(),
+                boosted: false,
+            })),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    /// 락을 잡으면 `Ok`, 이미 잡혀 있으면 owner를 `Err`로 반환
+    fn acquire(&self) -> Result<MutexGuard<'_, T>, Owner> {
+        interrupt::free(|cs| {
+            let mut state = self.state.borrow(cs).borrow_mut();
+            match state.owner {
+                Some(owner) => Err(owner),
+                None => {
+                    state.owner = Some(Owner::current());
+                    Ok(MutexGuard { mutex: self })
+                }
+            }
+        })
+    }
+
+    /// 바로 락을 시도합니다. (ISR에서 호출 가능, guard는 ISR 안에서 drop할 것)
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.acquire().ok()
+    }
+
+    /// 락을 시도하고, 실패하면 현재 태스크를 대기자로 등록합니다.
+    ///
+    /// 락이 풀리면 태스크에 `bits` 알림을 보내 다시 실행되게 하므로,
+    /// 실패한 태스크는 그냥 반환하고 다음 실행에서 다시 시도하면 됩니다.
+    pub fn try_lock_or_wait(&self, bits: u16) -> Option<MutexGuard<'_, T>> {
+        let registered = interrupt::free(|cs| {
+            let mut state = self.state.borrow(cs).borrow_mut();
+            if state.owner.is_none() {
+                state.owner = Some(Owner::current());
+                return None;
+            }
+            Some(state.waiters.add_current(bits))
+        });
+        match registered {
+            None => Some(MutexGuard { mutex: self }),
+            Some(true) => None,
+            Some(false) => {
+                notify_current_now(bits);
+                None
+            }
+        }
+    }
+
+    /// 락이 풀릴 때까지 기다려서 잡습니다.
+    ///
+    /// 기다리는 동안 다른 태스크를 실행합니다. 락을 가진 태스크가 지금 태스크 아래에서
+    /// 양보 중이라 풀릴 수 없으면 `SyncError::WouldDeadlock`을 반환합니다.
+    pub fn lock(&self) -> Result<MutexGuard<'_, T>, SyncError> {
+        let mut result = None;
+        block_until(|| {
+            match self.acquire() {
+                Ok(guard) => result = Some(Ok(guard)),
+                Err(owner) if owner.blocked_by_caller() => result = Some(Err(SyncError::WouldDeadlock)),
+                Err(owner) => self.inherit_priority(owner),
+            }
+            result.is_some()
+        });
+        result.unwrap_or(Err(SyncError::WouldDeadlock))
+    }
+
+    /// 기다리는 스레드의 우선순위를 owner 스레드에 상속
+    #[cfg(all(feature = "preemptive", target_arch = "avr"))]
+    fn inherit_priority(&self, owner: Owner) {
+        let Some(priority) = kernel::priority(kernel::current()) else {
+            return;
+        };
+        if kernel::priority(owner.thread).is_some_and(|owner_priority| owner_priority < priority)
+            && kernel::inherit_priority(owner.thread, self.key(), priority)
+        {
+            interrupt::free(|cs| self.state.borrow(cs).borrow_mut().boosted = true);
+        }
+    }
+
+    #[cfg(not(all(feature = "preemptive", target_arch = "avr")))]
+    fn inherit_priority(&self, _owner: Owner) {}
+
+    /// 커널이 락별 상속 우선순위를 구분하는 키 (이 락의 주소)
+    #[cfg(all(feature = "preemptive", target_arch = "avr"))]
+    fn key(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// 락이 잡혀 있는지
+    pub fn is_locked(&self) -> bool {
+        interrupt::free(|cs| self.state.borrow(cs).borrow().owner.is_some())
+    }
+
+    fn unlock(&self) {
+        let (waiters, _owner, _boosted) = interrupt::free(|cs| {
+            let mut state = self.state.borrow(cs).borrow_mut();
+            let owner = state.owner.take();
+            let boosted = core::mem::replace(&mut state.boosted, false);
+            (state.waiters.take(), owner, boosted)
+        });
+
+        #[cfg(all(feature = "preemptive", target_arch = "avr"))]
+        if let (true, Some(owner)) = (_boosted, _owner) {
+            kernel::restore_priority(owner.thread, self.key());
+        }
+        waiters.notify_all();
+    }
+}
+
+/// `Mutex`의 락을 가진 동안 데이터에 접근하는 guard (drop되면 락을 놓음)
+pub struct MutexGuard<'a, T> {
+    mutex: &'a Mutex<T>,
+}
+
+impl<T> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.unlock();
+    }
+}
+
+//
+// 카운팅 세마포어
+//
+struct SemaphoreState {
+    count: u8,
+    max: u8,
+    waiters: Waiters,
+}
+
+/// 카운팅 세마포어 (자원 개수 또는 ISR → 태스크 이벤트 횟수)
+///
+/// ```ignore
+/// static RX_LINES: sync::Semaphore = sync::Semaphore::new(0, 8);
+/// ```
+pub struct Semaphore(interrupt::Mutex<RefCell<SemaphoreState>>);
+
+impl Semaphore {
+    /// 초기 개수 `initial`, 최대 `max`
+    pub const fn new(initial: u8, max: u8) -> Self {
+        assert!(initial <= max, "semaphore initial count exceeds its maximum");
+        Self(interrupt::Mutex::new(RefCell::new(SemaphoreState {
+            count: initial,
+            max,
+            waiters: Waiters::new(),
+        })))
+    }
+
+    /// 하나를 바로 가져옵니다. (ISR에서 호출 가능)
+    pub fn try_acquire(&self) -> bool {
+        interrupt::free(|cs| {
+            let mut state = self.0.borrow(cs).borrow_mut();
+            if state.count == 0 {
+                return false;
+            }
+            state.count -= 1;
+            true
+        })
+    }
+
+    /// 하나를 가져오고, 없으면 현재 태스크를 대기자로 등록합니다.
+    ///
+    /// `release()`되면 태스크에 `bits` 알림을 보내 다시 실행되게 합니다.
+    pub fn try_acquire_or_wait(&self, bits: u16) -> bool {
+        let registered = interrupt::free(|cs| {
+            let mut state = self.0.borrow(cs).borrow_mut();
+            if state.count > 0 {
+                state.count -= 1;
+                return None;
+            }
+            Some(state.waiters.add_current(bits))
+        });
+        match registered {
+            None => true,
+            Some(true) => false,
+            Some(false) => {
+                notify_current_now(bits);
+                false
+            }
+        }
+    }
+
+    /// 하나를 가져올 때까지 다른 태스크를 실행하며 기다립니다.
+    pub fn acquire(&self) {
+        block_until(|| self.try_acquire());
+    }
+
+    /// 하나를 돌려놓습니다. (ISR에서 호출 가능) 이미 최대 개수면 `false`
+    pub fn release(&self) -> bool {
+        let waiters = interrupt::free(|cs| {
+            let mut state = self.0.borrow(cs).borrow_mut();
+            if state.count == state.max {
+                return None;
+            }
+            state.count += 1;
+            Some(state.waiters.take())
+        });
+        match waiters {
+            Some(waiters) => {
+                waiters.notify_all();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 남은 개수
+    pub fn available(&self) -> u8 {
+        interrupt::free(|cs| self.0.borrow(cs).borrow().count)
+    }
+}
+
+//
+// TryLock: 임계구역 없는 non-blocking 락
+//
+// AVR에는 compare-and-swap이 없어 읽기와 쓰기가 따로 일어나지만, ISR은 끝까지 실행된 뒤
+// 돌아오므로 태스크의 읽기/쓰기 사이에 끼어든 ISR은 락을 잡았다면 반드시 놓고 돌아옵니다.
+// 따라서 단일 코어 + ISR 비중첩에서는 인터럽트를 막지 않고도 상호 배제가 성립합니다.
+// 스레드 전환은 ISR 안에서 일어나 이 가정이 깨지므로 `preemptive` 기능에서는
+// 검사-설정 두 명령 동안만 인터럽트를 막습니다.
+//
+
+/// 플래그 하나로 동작하는 non-blocking 락 (ISR과 태스크 사이의 짧은 공유용)
+pub struct TryLock<T> {
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+// data는 락을 잡은 guard를 통해서만 접근합니다.
+unsafe impl<T: Send> Sync for TryLock<T> {}
+
+impl<T> TryLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    /// 락을 시도합니다. (ISR에서 호출 가능, 기다리지 않음)
+    pub fn try_lock(&self) -> Option<TryLockGuard<'_, T>> {
+        #[cfg(all(feature = "preemptive", target_arch = "avr"))]
+        let taken = interrupt::free(|_| self.test_and_set());
+        #[cfg(not(all(feature = "preemptive", target_arch = "avr")))]
+        let taken = self.test_and_set();
+        taken.then_some(TryLockGuard { lock: self })
+    }
+
+    fn test_and_set(&self) -> bool {
+        if self.locked.load(Ordering::Acquire) {
+            return false;
+        }
+        self.locked.store(true, Ordering::Release);
+        true
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+}
+
+/// `TryLock`의 guard (drop되면 락을 놓음)
+pub struct TryLockGuard<'a, T> {
+    lock: &'a TryLock<T>,
+}
+
+impl<T> Deref for TryLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for TryLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for TryLockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+    }
+}
+
+//
+// 호스트 시뮬레이션 테스트 (`cargo test-host`)
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::AtomicU32;
+
+    static SHARED: Mutex<u32> = Mutex::new(0);
+    static RUNS: AtomicU32 = AtomicU32::new(0);
+
+    fn setup() -> std::sync::MutexGuard<'static, ()> {
+        let guard = scheduler::tests::setup();
+        RUNS.store(0, Ordering::SeqCst);
+        interrupt::free(|cs| {
+            let mut state = SHARED.state.borrow(cs).borrow_mut();
+            state.owner = None;
+            state.waiters = Waiters::new();
+        });
+        guard
+    }
+
+    #[test]
+    fn waiter_is_notified_when_the_mutex_is_released() {
+        let _guard = setup();
+
+        let held = SHARED.try_lock().unwrap();
+        let waiter = scheduler::task_add_event(|| {
+            if let Some(mut value) = SHARED.try_lock_or_wait(1) {
+                *value += 1;
+                RUNS.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+        waiter.notify(0).unwrap();
+        scheduler::scheduler_run();
+        assert_eq!(RUNS.load(Ordering::SeqCst), 0);
+        assert_eq!(waiter.state(), Ok(TaskState::Waiting));
+
+        // 놓으면 대기 태스크가 ready가 되어 다시 시도
+        drop(held);
+        assert_eq!(waiter.state(), Ok(TaskState::Ready));
+        scheduler::scheduler_run();
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+        assert_eq!(*SHARED.try_lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn lock_held_across_a_yield_reports_deadlock() {
+        static RESULT: Mutex<Option<Result<(), SyncError>>> = Mutex::new(None);
+        let _guard = setup();
+        *RESULT.try_lock().unwrap() = None;
+
+        // 1ms 태스크가 락을 잡고 양보하는 동안, 중첩 실행된 태스크가 같은 락을 기다림
+        scheduler::task_add(
+            || {
+                let _held = SHARED.lock().unwrap();
+                scheduler::delay_yield(3);
+            },
+            10,
+        )
+        .unwrap();
+        scheduler::task_add(
+            || {
+                if SHARED.is_locked() {
+                    let result = SHARED.lock().map(|_| ());
+                    *RESULT.try_lock().unwrap() = Some(result);
+                }
+            },
+            1,
+        )
+        .unwrap();
+        scheduler::tests::run_until(13);
+
+        assert_eq!(*RESULT.try_lock().unwrap(), Some(Err(SyncError::WouldDeadlock)));
+        assert!(!SHARED.is_locked());
+    }
+
+    #[test]
+    fn semaphore_counts_releases_and_wakes_acquire() {
+        static LINES: Semaphore = Semaphore::new(0, 2);
+        let _guard = setup();
+        while LINES.try_acquire() {}
+
+        assert!(LINES.release());
+        assert!(LINES.release());
+        assert!(!LINES.release());
+        assert_eq!(LINES.available(), 2);
+
+        // 비어 있으면 release될 때까지 다른 태스크/슬립으로 기다림
+        assert!(LINES.try_acquire());
+        assert!(LINES.try_acquire());
+        scheduler::task_add(|| { LINES.release(); }, 5).unwrap();
+        LINES.acquire();
+        assert_eq!(scheduler::millis(), 5);
+        assert_eq!(LINES.available(), 0);
+    }
+
+    #[test]
+    fn semaphore_waiter_is_notified_on_release() {
+        static ITEMS: Semaphore = Semaphore::new(0, 4);
+        let _guard = setup();
+        while ITEMS.try_acquire() {}
+
+        let consumer = scheduler::task_add_event(|| {
+            if ITEMS.try_acquire_or_wait(1) {
+                RUNS.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+        consumer.notify(0).unwrap();
+        scheduler::scheduler_run();
+        assert_eq!(RUNS.load(Ordering::SeqCst), 0);
+        assert_eq!(consumer.state(), Ok(TaskState::Waiting));
+
+        assert!(ITEMS.release());
+        assert_eq!(consumer.state(), Ok(TaskState::Ready));
+        scheduler::scheduler_run();
+        assert_eq!(RUNS.load(Ordering::SeqCst), 1);
+        assert_eq!(ITEMS.available(), 0);
+    }
+
+    #[test]
+    fn try_lock_is_exclusive() {
+        static FLAG: TryLock<u8> = TryLock::new(0);
+        let _guard = setup();
+
+        let mut first = FLAG.try_lock().unwrap();
+        *first = 7;
+        assert!(FLAG.try_lock().is_none());
+        drop(first);
+        assert_eq!(*FLAG.try_lock().unwrap(), 7);
+        assert!(!FLAG.is_locked());
+    }
+}
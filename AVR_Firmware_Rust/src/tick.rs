@@ -0,0 +1,220 @@
+
+//
+// 스케줄러 tick 소스 설정
+//
+// tick 타이머와 주기는 Cargo 기능으로 선택합니다. (소스 코드를 고칠 필요 없음)
+//   - 타이머: 기본 Timer0, `tick-timer1` / `tick-timer2`
+//   - 주기:   기본 1ms, `tick-100us` / `tick-250us` / `tick-500us` / `tick-2ms` / `tick-4ms` / `tick-10ms`
+// 분주비/비교값은 `TickConfig::new()`가 CPU 클록으로부터 컴파일 타임에 계산하며,
+// 정확히 만들 수 없는 조합이면 컴파일 에러가 납니다.
+//
+//   (기본)                          →  Timer0, 분주 64, OCR0A = 249
+//   tick-timer2, tick-100us         →  Timer2, 분주 8,  OCR2A = 199
+//   tick-timer1, tick-10ms          →  Timer1, 분주 8,  OCR1A = 19999
+//
+// `preemptive` 커널은 문맥 전환 ISR이 TIMER0_COMPA에 고정되어 있어 Timer0만 사용할 수 있습니다.
+//
+use crate::hal::pac;
+
+#[cfg(all(feature = "tick-timer1", feature = "tick-timer2"))]
+compile_error!("select only one of the `tick-timer1` / `tick-timer2` features");
+
+/// tick으로 사용할 타이머 (`tick-timer*` 기능)
+pub const TICK_SOURCE: TickSource = if cfg!(feature = "tick-timer1") {
+    TickSource::Timer1
+} else if cfg!(feature = "tick-timer2") {
+    TickSource::Timer2
+} else {
+    TickSource::Timer0
+};
+
+/// tick 주기 (us, `tick-*` 주기 기능)
+pub const TICK_US: u32 = {
+    const RATES: [(bool, u32); 6] = [
+        (cfg!(feature = "tick-100us"), 100),
+        (cfg!(feature = "tick-250us"), 250),
+        (cfg!(feature = "tick-500us"), 500),
+        (cfg!(feature = "tick-2ms"), 2000),
+        (cfg!(feature = "tick-4ms"), 4000),
+        (cfg!(feature = "tick-10ms"), 10_000),
+    ];
+    let mut tick_us = 1000;
+    let mut selected = 0;
+    let mut i = 0;
+    while i < RATES.len() {
+        if RATES[i].0 {
+            tick_us = RATES[i].1;
+            selected += 1;
+        }
+        i += 1;
+    }
+    assert!(selected <= 1, "select only one tick rate feature");
+    tick_us
+};
+
+/// CPU 클록 주파수 (16MHz 기준, serial 보율 계산에도 사용)
+pub const CPU_FREQUENCY: u32 = 16_000_000;
+
+/// 컴파일 타임에 계산된 tick 설정
+pub const TICK: TickConfig = TickConfig::new(TICK_SOURCE, TICK_US);
+
+/// tick 소스로 쓸 수 있는 타이머
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TickSource {
+    /// 8비트 Timer0 (TIMER0_COMPA)
+    Timer0,
+    /// 16비트 Timer1 (TIMER1_COMPA)
+    Timer1,
+    /// 8비트 Timer2 (TIMER2_COMPA)
+    Timer2,
+}
+
+/// CTC 모드 설정값 (분주비, CSn 비트, 비교값)
+///
+/// `idle_*`는 tickless idle(`scheduler`의 유휴 슬립)에서 비교 주기를 늘릴 때 쓰는 설정입니다.
+/// 카운트 1번이 정확히 정수 us가 되는 가장 큰 분주비를 골라, 늘린 구간도 오차 없이 셉니다.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TickConfig {
+    pub source: TickSource,
+    pub prescaler: u32,
+    pub cs_bits: u8,
+    pub compare: u16,
+    pub idle_prescaler: u32,
+    pub idle_cs_bits: u8,
+    /// 한 번에 셀 수 있는 최대 카운트 수 (8비트 256, 16비트 65536)
+    pub max_counts: u32,
+}
+
+// (분주비, CSn 비트) 목록
+const TIMER01_PRESCALERS: [(u32, u8); 5] = [(1, 1), (8, 2), (64, 3), (256, 4), (1024, 5)];
+const TIMER2_PRESCALERS: [(u32, u8); 7] = [(1, 1), (8, 2), (32, 3), (64, 4), (128, 5), (256, 6), (1024, 7)];
+
+impl TickConfig {
+    /// `tick_us` 주기를 정확히 만드는 가장 작은 분주비를 선택합니다.
+    /// (작은 분주비일수록 `micros()` 분해능이 좋음)
+    pub const fn new(source: TickSource, tick_us: u32) -> Self {
+        let (prescalers, max_count): (&[(u32, u8)], u64) = match source {
+            TickSource::Timer0 => (&TIMER01_PRESCALERS, 256),
+            TickSource::Timer1 => (&TIMER01_PRESCALERS, 65536),
+            TickSource::Timer2 => (&TIMER2_PRESCALERS, 256),
+        };
+
+        let cycles = CPU_FREQUENCY as u64 * tick_us as u64;
+        assert!(tick_us > 0 && cycles.is_multiple_of(1_000_000), "tick period is not a whole number of CPU cycles");
+        let cycles = cycles / 1_000_000;
+
+        // tickless idle용: 카운트 1번이 정수 us인 가장 큰 분주비
+        let mut idle = 0;
+        let mut i = 0;
+        while i < prescalers.len() {
+            if prescalers[i].0.is_multiple_of(CPU_FREQUENCY / 1_000_000) {
+                idle = i;
+            }
+            i += 1;
+        }
+        let (idle_prescaler, idle_cs_bits) = prescalers[idle];
+        assert!(idle_prescaler.is_multiple_of(CPU_FREQUENCY / 1_000_000), "no prescaler gives whole-us counts");
+
+        let mut i = 0;
+        while i < prescalers.len() {
+            let (prescaler, cs_bits) = prescalers[i];
+            if cycles.is_multiple_of(prescaler as u64) {
+                let counts = cycles / prescaler as u64;
+                if counts >= 2 && counts <= max_count {
+                    return Self {
+                        source,
+                        prescaler,
+                        cs_bits,
+                        compare: (counts - 1) as u16,
+                        idle_prescaler,
+                        idle_cs_bits,
+                        max_counts: max_count as u32,
+                    };
+                }
+            }
+            i += 1;
+        }
+        panic!("tick period cannot be generated exactly by the selected timer");
+    }
+
+    /// 타이머 카운트 값을 us로 변환
+    pub const fn counts_to_us(&self, counts: u32) -> u32 {
+        counts * self.prescaler / (CPU_FREQUENCY / 1_000_000)
+    }
+
+    /// us를 타이머 카운트 값으로 변환 (내림)
+    pub const fn us_to_counts(&self, us: u32) -> u32 {
+        (us as u64 * (CPU_FREQUENCY / 1_000_000) as u64 / self.prescaler as u64) as u32
+    }
+
+    /// tickless idle 설정에서 카운트 1번의 시간 (us)
+    pub const fn idle_count_us(&self) -> u32 {
+        self.idle_prescaler / (CPU_FREQUENCY / 1_000_000)
+    }
+}
+
+/// tick 소스로 넘겨받은 타이머 주변장치
+pub enum TickTimer {
+    Timer0(pac::TC0),
+    Timer1(pac::TC1),
+    Timer2(pac::TC2),
+}
+
+impl From<pac::TC0> for TickTimer {
+    fn from(tc0: pac::TC0) -> Self {
+        TickTimer::Timer0(tc0)
+    }
+}
+
+impl From<pac::TC1> for TickTimer {
+    fn from(tc1: pac::TC1) -> Self {
+        TickTimer::Timer1(tc1)
+    }
+}
+
+impl From<pac::TC2> for TickTimer {
+    fn from(tc2: pac::TC2) -> Self {
+        TickTimer::Timer2(tc2)
+    }
+}
+
+// 레지스터 설정/읽기(configure, counter, compare_pending)는 `hal` 백엔드가 구현합니다.
+impl TickTimer {
+    pub fn source(&self) -> TickSource {
+        match self {
+            TickTimer::Timer0(_) => TickSource::Timer0,
+            TickTimer::Timer1(_) => TickSource::Timer1,
+            TickTimer::Timer2(_) => TickSource::Timer2,
+        }
+    }
+}
+
+//
+// tick 인터럽트: 선택한 타이머의 비교 매치 벡터만 정의합니다.
+// (호스트 시뮬레이션에서는 `hal::sim`이 tick을 직접 발생)
+//
+
+/// Timer0 비교 매치 인터럽트
+///
+/// `preemptive` 기능을 켜면 이 벡터는 `kernel` 모듈이 문맥 전환용으로 직접 정의하고,
+/// 그 안에서 `scheduler::tick()`을 호출합니다.
+#[cfg(all(
+    target_arch = "avr",
+    not(any(feature = "preemptive", feature = "tick-timer1", feature = "tick-timer2"))
+))]
+#[avr_device::interrupt(atmega2560)]
+fn TIMER0_COMPA() {
+    crate::hal::interrupt::free(crate::scheduler::tick);
+}
+
+#[cfg(all(target_arch = "avr", feature = "tick-timer1"))]
+#[avr_device::interrupt(atmega2560)]
+fn TIMER1_COMPA() {
+    crate::hal::interrupt::free(crate::scheduler::tick);
+}
+
+#[cfg(all(target_arch = "avr", feature = "tick-timer2"))]
+#[avr_device::interrupt(atmega2560)]
+fn TIMER2_COMPA() {
+    crate::hal::interrupt::free(crate::scheduler::tick);
+}
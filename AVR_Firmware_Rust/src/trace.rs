@@ -0,0 +1,456 @@
+//
+// 스케줄러 트레이스 기록기 (`trace` 기능)
+//
+// - 태스크 시작/종료, ISR 진입/종료, 사용자 마커를 `micros()` 타임스탬프와 함께
+//   RAM 링버퍼(TRACE_CAPACITY개)에 기록합니다. 가득 차면 가장 오래된 이벤트를 덮어씁니다.
+// - 내장 ISR(tick, USART0 UDRE)은 매 ms/매 바이트마다 이벤트 2개를 써서 버퍼를 금방 채우므로
+//   `trace-isr` 기능을 켰을 때만 기록합니다. (사용자 ISR의 `isr_enter/isr_exit`는 항상 기록)
+// - `start_dump()` 후 `dump_poll()`을 반복 호출하면 serial로 바이너리 덤프를 내보냅니다.
+//   (송신 링버퍼에 들어가는 만큼씩 나눠 보내므로 다른 태스크를 막지 않음)
+// - 호스트에서 `tools/trace_to_json.py`로 Chrome trace / Perfetto JSON으로 변환합니다.
+//
+// `trace` 기능이 꺼져 있으면 기록 함수는 모두 빈 함수가 되어 RAM/실행 시간을 쓰지 않습니다.
+//
+// 덤프 형식 (little-endian)
+//   헤더  (8바이트): 'T' 'R' 'C' '1' | 이벤트 수 u16 | 덮어써서 잃은 이벤트 수 u16
+//   이벤트 (6바이트): 타임스탬프(us) u32 | 종류 u8 | id u8   (오래된 것부터)
+//     종류 0/1 = 태스크 시작/종료 (id = 태스크 슬롯)
+//     종류 2/3 = ISR 진입/종료   (id = ISR_* 번호)
+//     종류 4   = 사용자 마커     (id = 마커 번호)
+//
+// 덤프는 다른 태스크의 serial 출력 사이에 섞여 나가므로 최대 16바이트씩 프레임으로 나눠 보냅니다.
+// 프레임 하나는 송신 버퍼에 한 번에 넣어 중간에 다른 출력이 끼어들지 않습니다.
+//   프레임: 0xA5 0x5A | 덤프 번호 u8 | 덤프 내 위치 u16 | 길이 u8 | 데이터 | CRC-8 (덤프 번호~데이터)
+// 변환기는 sync 바이트와 CRC로 프레임을 찾아 그 사이의 텍스트는 건너뜁니다.
+//
+
+#![cfg_attr(not(test), allow(dead_code))]
+
+/// tick 타이머 비교 매치 인터럽트
+pub const ISR_TICK: u8 = 0;
+/// USART0 송신 버퍼 빔 인터럽트
+pub const ISR_USART0_UDRE: u8 = 1;
+/// 사용자 ISR에 쓸 수 있는 첫 번호 (`isr_enter(ISR_USER + n)`)
+pub const ISR_USER: u8 = 16;
+
+const KIND_TASK_START: u8 = 0;
+const KIND_TASK_END: u8 = 1;
+const KIND_ISR_ENTER: u8 = 2;
+const KIND_ISR_EXIT: u8 = 3;
+const KIND_MARKER: u8 = 4;
+
+/// 태스크 실행 시작 (`scheduler_run()`이 호출)
+#[inline(always)]
+pub fn task_start(slot: u8) {
+    record(KIND_TASK_START, slot);
+}
+
+/// 태스크 실행 종료 (`scheduler_run()`이 호출)
+#[inline(always)]
+pub fn task_end(slot: u8) {
+    record(KIND_TASK_END, slot);
+}
+
+/// ISR 진입 (ISR 본문 맨 앞에서 호출)
+#[inline(always)]
+pub fn isr_enter(id: u8) {
+    record(KIND_ISR_ENTER, id);
+}
+
+/// ISR 종료 (ISR 본문 맨 끝에서 호출)
+#[inline(always)]
+pub fn isr_exit(id: u8) {
+    record(KIND_ISR_EXIT, id);
+}
+
+/// 내장 ISR 진입 (`trace-isr` 기능을 켰을 때만 기록)
+#[inline(always)]
+pub(crate) fn builtin_isr_enter(id: u8) {
+    if cfg!(feature = "trace-isr") {
+        isr_enter(id);
+    }
+}
+
+/// 내장 ISR 종료 (`trace-isr` 기능을 켰을 때만 기록)
+#[inline(always)]
+pub(crate) fn builtin_isr_exit(id: u8) {
+    if cfg!(feature = "trace-isr") {
+        isr_exit(id);
+    }
+}
+
+/// 사용자 마커 (타임라인에 순간 이벤트로 표시)
+#[inline(always)]
+pub fn marker(id: u8) {
+    record(KIND_MARKER, id);
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline(always)]
+fn record(_kind: u8, _id: u8) {}
+
+#[cfg(feature = "trace")]
+#[allow(unused_imports)]
+pub use recorder::{clear, dump_poll, start_dump};
+
+#[cfg(feature = "trace")]
+use recorder::record;
+
+#[cfg(feature = "trace")]
+mod recorder {
+    use core::cell::RefCell;
+
+    use crate::hal::interrupt::{self, Mutex};
+    use crate::{scheduler, serial};
+
+    /// 링버퍼에 보관하는 이벤트 수 (이벤트당 6바이트)
+    const TRACE_CAPACITY: usize = 128;
+    const HEADER_SIZE: usize = 8;
+    const EVENT_SIZE: usize = 6;
+    const MAGIC: [u8; 4] = *b"TRC1";
+    /// 프레임 시작 표시 (ASCII 텍스트에 나오지 않는 값)
+    const FRAME_SYNC: [u8; 2] = [0xA5, 0x5A];
+    /// 프레임 하나에 담는 최대 덤프 바이트 수
+    const FRAME_PAYLOAD: usize = 16;
+    /// sync(2) + 덤프 번호(1) + 위치(2) + 길이(1) + 데이터 + CRC(1)
+    const FRAME_OVERHEAD: usize = 7;
+
+    #[derive(Copy, Clone)]
+    struct TraceEvent {
+        timestamp_us: u32,
+        kind: u8,
+        id: u8,
+    }
+
+    struct TraceBuffer {
+        events: [TraceEvent; TRACE_CAPACITY],
+        /// 가장 오래된 이벤트 위치
+        head: usize,
+        len: usize,
+        /// 덮어써서 잃은 이벤트 수
+        lost: u16,
+        /// 덤프 중이면 지금까지 보낸 바이트 수 (덤프 중에는 기록을 멈춰 버퍼를 고정)
+        dump_sent: Option<usize>,
+        /// 덤프 번호 (변환기가 덤프 경계를 구분)
+        dump_seq: u8,
+    }
+
+    impl TraceBuffer {
+        const fn new() -> Self {
+            Self {
+                events: [TraceEvent { timestamp_us: 0, kind: 0, id: 0 }; TRACE_CAPACITY],
+                head: 0,
+                len: 0,
+                lost: 0,
+                dump_sent: None,
+                dump_seq: 0,
+            }
+        }
+
+        fn push(&mut self, event: TraceEvent) {
+            let tail = (self.head + self.len) % TRACE_CAPACITY;
+            self.events[tail] = event;
+            if self.len == TRACE_CAPACITY {
+                self.head = (self.head + 1) % TRACE_CAPACITY;
+                self.lost = self.lost.saturating_add(1);
+            } else {
+                self.len += 1;
+            }
+        }
+
+        fn dump_size(&self) -> usize {
+            HEADER_SIZE + self.len * EVENT_SIZE
+        }
+
+        /// 덤프 바이트열의 `offset`번째 바이트
+        fn dump_byte(&self, offset: usize) -> u8 {
+            if offset < HEADER_SIZE {
+                let len = self.len as u16;
+                let header = [
+                    MAGIC[0], MAGIC[1], MAGIC[2], MAGIC[3],
+                    len as u8, (len >> 8) as u8,
+                    self.lost as u8, (self.lost >> 8) as u8,
+                ];
+                return header[offset];
+            }
+            let index = (offset - HEADER_SIZE) / EVENT_SIZE;
+            let event = self.events[(self.head + index) % TRACE_CAPACITY];
+            let ts = event.timestamp_us.to_le_bytes();
+            [ts[0], ts[1], ts[2], ts[3], event.kind, event.id][(offset - HEADER_SIZE) % EVENT_SIZE]
+        }
+    }
+
+    static TRACE: Mutex<RefCell<TraceBuffer>> = Mutex::new(RefCell::new(TraceBuffer::new()));
+
+    pub(super) fn record(kind: u8, id: u8) {
+        let timestamp_us = scheduler::micros();
+        interrupt::free(|cs| {
+            let mut trace = TRACE.borrow(cs).borrow_mut();
+            if trace.dump_sent.is_none() {
+                trace.push(TraceEvent { timestamp_us, kind, id });
+            }
+        });
+    }
+
+    /// 기록된 이벤트를 모두 지웁니다.
+    pub fn clear() {
+        interrupt::free(|cs| {
+            let mut trace = TRACE.borrow(cs).borrow_mut();
+            trace.head = 0;
+            trace.len = 0;
+            trace.lost = 0;
+        });
+    }
+
+    /// 덤프 시작: 기록을 멈추고 현재 버퍼를 serial로 내보낼 준비
+    pub fn start_dump() {
+        interrupt::free(|cs| TRACE.borrow(cs).borrow_mut().dump_sent = Some(0));
+    }
+
+    /// 덤프를 송신 링버퍼에 들어가는 만큼 프레임 단위로 이어서 보냅니다.
+    ///
+    /// 덤프가 끝나면(또는 진행 중인 덤프가 없으면) `true`를 반환하며,
+    /// 이때 버퍼를 비우고 기록을 다시 시작합니다.
+    pub fn dump_poll() -> bool {
+        loop {
+            // 임계구역에서 프레임만 만들고, 송신은 밖에서
+            let mut frame = [0u8; FRAME_PAYLOAD + FRAME_OVERHEAD];
+            let (frame_len, done) = interrupt::free(|cs| {
+                let trace = TRACE.borrow(cs).borrow();
+                let Some(sent) = trace.dump_sent else {
+                    return (0, true);
+                };
+                let len = (trace.dump_size() - sent).min(FRAME_PAYLOAD);
+                let offset = sent as u16;
+                frame[..6].copy_from_slice(&[
+                    FRAME_SYNC[0], FRAME_SYNC[1],
+                    trace.dump_seq,
+                    offset as u8, (offset >> 8) as u8,
+                    len as u8,
+                ]);
+                for (i, byte) in frame[6..6 + len].iter_mut().enumerate() {
+                    *byte = trace.dump_byte(sent + i);
+                }
+                frame[6 + len] = crc8(&frame[2..6 + len]);
+                (len + FRAME_OVERHEAD, false)
+            });
+            if done {
+                return true;
+            }
+
+            // 프레임이 통째로 들어갈 자리가 없으면 다음 호출에서 다시 시도
+            if !serial::try_write_bytes(&frame[..frame_len]) {
+                return false;
+            }
+
+            let finished = interrupt::free(|cs| {
+                let mut trace = TRACE.borrow(cs).borrow_mut();
+                let sent = trace.dump_sent.unwrap_or(0) + frame_len - FRAME_OVERHEAD;
+                if sent >= trace.dump_size() {
+                    let next_seq = trace.dump_seq.wrapping_add(1);
+                    *trace = TraceBuffer::new();
+                    trace.dump_seq = next_seq;
+                    true
+                } else {
+                    trace.dump_sent = Some(sent);
+                    false
+                }
+            });
+            if finished {
+                return true;
+            }
+        }
+    }
+
+    /// CRC-8 (다항식 0x07, 초기값 0)
+    fn crc8(bytes: &[u8]) -> u8 {
+        let mut crc = 0u8;
+        for &byte in bytes {
+            crc ^= byte;
+            for _ in 0..8 {
+                crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+            }
+        }
+        crc
+    }
+
+    //
+    // 호스트 시뮬레이션 테스트 (`cargo test-host --features trace`)
+    //
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::hal::{pac, sim};
+        use crate::trace::{
+            isr_enter, isr_exit, marker, task_end, task_start, ISR_USART0_UDRE, ISR_USER, KIND_ISR_ENTER,
+            KIND_ISR_EXIT, KIND_MARKER, KIND_TASK_END, KIND_TASK_START,
+        };
+
+        fn setup() -> std::sync::MutexGuard<'static, ()> {
+            let guard = sim::lock();
+            sim::reset();
+            serial::serial_init(pac::Peripherals::take().unwrap().USART0, 115200);
+            interrupt::free(|cs| *TRACE.borrow(cs).borrow_mut() = TraceBuffer::new());
+            guard
+        }
+
+        /// 덤프가 끝날 때까지 보내고 serial 출력을 모음
+        fn dump() -> std::vec::Vec<u8> {
+            let mut out = std::vec::Vec::new();
+            start_dump();
+            loop {
+                let done = dump_poll();
+                out.extend(sent());
+                if done {
+                    return out;
+                }
+            }
+        }
+
+        /// 지금까지 송신된 serial 출력
+        fn sent() -> std::vec::Vec<u8> {
+            let mut buf = [0u8; 1024];
+            sim::serial_flush();
+            let len = sim::serial_take_output(&mut buf);
+            buf[..len].to_vec()
+        }
+
+        /// serial 출력에서 CRC가 맞는 프레임만 골라 (덤프 번호, 이어 붙인 덤프)로 복원
+        fn unframe(out: &[u8]) -> (u8, std::vec::Vec<u8>) {
+            let mut seq = None;
+            let mut data = std::vec::Vec::new();
+            let mut pos = 0;
+            while pos + FRAME_OVERHEAD <= out.len() {
+                let len = out[pos + 5] as usize;
+                let end = pos + 6 + len;
+                if out[pos..pos + 2] == FRAME_SYNC
+                    && len <= FRAME_PAYLOAD
+                    && end < out.len()
+                    && crc8(&out[pos + 2..end]) == out[end]
+                {
+                    assert_eq!(u16::from_le_bytes([out[pos + 3], out[pos + 4]]) as usize, data.len());
+                    assert_eq!(*seq.get_or_insert(out[pos + 2]), out[pos + 2]);
+                    data.extend_from_slice(&out[pos + 6..end]);
+                    pos = end + 1;
+                } else {
+                    pos += 1;
+                }
+            }
+            (seq.unwrap(), data)
+        }
+
+        #[test]
+        fn dump_has_header_and_events_in_order() {
+            let _guard = setup();
+
+            marker(7);
+            task_start(2);
+            isr_enter(ISR_USER + 1);
+            isr_exit(ISR_USER + 1);
+            task_end(2);
+            let (_, out) = unframe(&dump());
+
+            assert_eq!(&out[..4], b"TRC1");
+            assert_eq!(u16::from_le_bytes([out[4], out[5]]), 5);
+            assert_eq!(u16::from_le_bytes([out[6], out[7]]), 0);
+            let events: std::vec::Vec<(u8, u8)> = out[HEADER_SIZE..]
+                .chunks(EVENT_SIZE)
+                .map(|e| (e[4], e[5]))
+                .collect();
+            assert_eq!(
+                events,
+                [
+                    (KIND_MARKER, 7),
+                    (KIND_TASK_START, 2),
+                    (KIND_ISR_ENTER, ISR_USER + 1),
+                    (KIND_ISR_EXIT, ISR_USER + 1),
+                    (KIND_TASK_END, 2),
+                ]
+            );
+        }
+
+        #[test]
+        fn oldest_events_are_overwritten() {
+            let _guard = setup();
+
+            for id in 0..(TRACE_CAPACITY + 3) {
+                marker(id as u8);
+            }
+            let (first, out) = unframe(&dump());
+
+            assert_eq!(out.len(), HEADER_SIZE + TRACE_CAPACITY * EVENT_SIZE);
+            assert_eq!(u16::from_le_bytes([out[6], out[7]]), 3);
+            assert_eq!(out[HEADER_SIZE + 5], 3);
+            // 덤프가 끝나면 버퍼를 비우고 다시 기록 (내장 ISR은 `trace-isr`에서만 기록)
+            let (second, out) = unframe(&dump());
+            assert_eq!(second, first.wrapping_add(1));
+            let expected = if cfg!(feature = "trace-isr") { out[HEADER_SIZE..].len() / EVENT_SIZE } else { 0 };
+            assert_eq!(u16::from_le_bytes([out[4], out[5]]) as usize, expected);
+            assert!(out[HEADER_SIZE..].chunks(EVENT_SIZE).all(|e| e[5] == ISR_USART0_UDRE));
+        }
+
+        #[test]
+        fn clear_drops_recorded_events() {
+            let _guard = setup();
+
+            for id in 0..(TRACE_CAPACITY + 1) {
+                marker(id as u8);
+            }
+            clear();
+            marker(9);
+            let (_, out) = unframe(&dump());
+
+            assert_eq!(u16::from_le_bytes([out[4], out[5]]), 1);
+            assert_eq!(u16::from_le_bytes([out[6], out[7]]), 0);
+            assert_eq!(out[HEADER_SIZE + 5], 9);
+        }
+
+        #[test]
+        fn other_serial_output_never_splits_a_frame() {
+            let _guard = setup();
+
+            for id in 0..40 {
+                marker(id);
+            }
+            start_dump();
+            let mut out = std::vec::Vec::new();
+            loop {
+                // 다른 태스크의 출력이 덤프 사이사이에 끼어듦
+                serial::write_str("10ms_Task!\r\n");
+                let done = dump_poll();
+                out.extend(sent());
+                if done {
+                    break;
+                }
+            }
+
+            let (_, dump) = unframe(&out);
+            assert_eq!(dump.len(), HEADER_SIZE + 40 * EVENT_SIZE);
+            assert!(dump[HEADER_SIZE..].chunks(EVENT_SIZE).map(|e| e[5]).eq(0..40));
+            assert!(out.windows(12).any(|w| w == b"10ms_Task!\r\n"));
+        }
+    }
+}
+
+//
+// `trace` 기능이 꺼진 빌드 (`cargo test-host`)
+//
+#[cfg(all(test, not(feature = "trace")))]
+mod tests {
+    use super::*;
+    use crate::hal::sim;
+
+    #[test]
+    fn events_are_dropped_without_the_feature() {
+        let _guard = sim::lock();
+        sim::reset();
+
+        isr_enter(ISR_USER);
+        marker(1);
+        isr_exit(ISR_USER);
+
+        // 링버퍼도 덤프 API도 없고, 아무것도 내보내지 않음
+        let mut buf = [0u8; 16];
+        assert_eq!(sim::serial_take_output(&mut buf), 0);
+    }
+}
@@ -0,0 +1,432 @@
+
+//
+// 태스크별 watchdog 감시
+//
+// - 하드웨어 WDT는 System Reset 모드로 켜 두고, 스케줄러 tick(ms)마다 `wdr`로 리셋합니다.
+// - 단, 감시 대상으로 등록된 태스크 중 하나라도 budget 안에 `checkin()`하지 않으면
+//   어느 태스크가 멈췄는지 `.noinit` RAM에 기록한 뒤 더 이상 `wdr`을 하지 않습니다.
+//   → WDT 타임아웃으로 MCU가 리셋되고, 재부팅 후 `last_stall()`로 원인을 확인할 수 있습니다.
+// - 인터럽트가 꺼진 채로 멈춘 경우에도 tick이 돌지 않으므로 WDT가 리셋합니다.
+//
+// 주의: WDT 리셋 후에는 WDT가 가장 짧은 주기로 켜진 상태로 부팅하므로
+//       `init()`을 main 초반에 호출해야 합니다.
+//
+#![cfg_attr(not(test), allow(dead_code))]
+
+use crate::hal::{self, pac};
+use crate::hal::interrupt::{self, CriticalSection, Mutex};
+use core::cell::{RefCell, UnsafeCell};
+use core::mem::MaybeUninit;
+
+use crate::scheduler::{self, TaskHandle};
+
+//
+// WDTCSR 비트 (데이터시트 12.5)
+//
+const WDP3: u8 = 5;
+const WDE: u8 = 3;
+
+/// 감시할 수 있는 최대 태스크 수 (스케줄러 태스크 테이블 크기와 같음)
+const MAX_SUPERVISED: usize = scheduler::MAX_TASKS;
+
+/// 하드웨어 WDT 타임아웃 (WDP3..0)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WatchdogTimeout {
+    Ms16 = 0,
+    Ms32 = 1,
+    Ms64 = 2,
+    Ms125 = 3,
+    Ms250 = 4,
+    Ms500 = 5,
+    S1 = 6,
+    S2 = 7,
+    S4 = 8,
+    S8 = 9,
+}
+
+impl WatchdogTimeout {
+    /// 타임아웃 (ms, 128kHz WDT 발진기 기준 근삿값)
+    pub const fn as_ms(self) -> u32 {
+        16 << self as u32
+    }
+}
+
+/// 리셋 직전에 기록된 정지 태스크 정보
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StallRecord {
+    /// budget 안에 check-in하지 못한 태스크의 슬롯 번호 / id
+    pub task_slot: u8,
+    pub task_id: u16,
+    /// 감지 시점에 `scheduler_run()`이 실행 중이던 태스크 (없으면 None)
+    pub running_slot: Option<u8>,
+    /// 감지 시각 (`millis()`)
+    pub uptime_ms: u32,
+}
+
+//
+// 리셋 후에도 유지되는 RAM 영역 (.noinit: 스타트업 코드가 0으로 초기화하지 않음)
+//
+const STALL_MAGIC: u16 = 0x5DA7;
+const NO_RUNNING_TASK: u8 = 0xFF;
+
+/// `.noinit`에 남기는 정지 기록
+///
+/// 전원 투입 직후의 RAM 내용은 정해져 있지 않으므로 바이트 배열로만 저장하고,
+/// magic 값을 확인한 뒤에만 나머지 바이트를 해석합니다.
+#[derive(Copy, Clone)]
+struct PersistedStall {
+    task_slot: u8,
+    task_id: u16,
+    running_slot: u8,
+    uptime_ms: u32,
+}
+
+/// magic(2) + task_slot(1) + task_id(2) + running_slot(1) + uptime_ms(4)
+const PERSISTED_LEN: usize = 10;
+
+impl PersistedStall {
+    fn to_bytes(self) -> [u8; PERSISTED_LEN] {
+        let mut bytes = [0; PERSISTED_LEN];
+        bytes[0..2].copy_from_slice(&STALL_MAGIC.to_le_bytes());
+        bytes[2] = self.task_slot;
+        bytes[3..5].copy_from_slice(&self.task_id.to_le_bytes());
+        bytes[5] = self.running_slot;
+        bytes[6..10].copy_from_slice(&self.uptime_ms.to_le_bytes());
+        bytes
+    }
+
+    /// magic이 맞을 때만 기록으로 해석
+    fn from_bytes(bytes: &[u8; PERSISTED_LEN]) -> Option<Self> {
+        if u16::from_le_bytes([bytes[0], bytes[1]]) != STALL_MAGIC {
+            return None;
+        }
+        Some(Self {
+            task_slot: bytes[2],
+            task_id: u16::from_le_bytes([bytes[3], bytes[4]]),
+            running_slot: bytes[5],
+            uptime_ms: u32::from_le_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]),
+        })
+    }
+}
+
+struct NoInit(UnsafeCell<MaybeUninit<[u8; PERSISTED_LEN]>>);
+
+// 인터럽트를 막은 상태(interrupt::free)에서만 접근합니다.
+unsafe impl Sync for NoInit {}
+
+// AVR에서는 리셋 후에도 내용이 유지되는 `.noinit`, 호스트 시뮬레이션에서는 0으로 초기화
+#[cfg(target_arch = "avr")]
+#[unsafe(link_section = ".noinit")]
+static PERSISTED_STALL: NoInit = NoInit(UnsafeCell::new(MaybeUninit::uninit()));
+#[cfg(not(target_arch = "avr"))]
+static PERSISTED_STALL: NoInit = NoInit(UnsafeCell::new(MaybeUninit::new([0; PERSISTED_LEN])));
+
+/// `.noinit` 영역을 바이트 단위로 읽음 (컴파일러가 값을 가정하지 못하도록 volatile)
+fn read_persisted() -> [u8; PERSISTED_LEN] {
+    let base = PERSISTED_STALL.0.get() as *const u8;
+    let mut bytes = [0; PERSISTED_LEN];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = unsafe { base.add(i).read_volatile() };
+    }
+    bytes
+}
+
+fn write_persisted(bytes: [u8; PERSISTED_LEN]) {
+    let base = PERSISTED_STALL.0.get() as *mut u8;
+    for (i, byte) in bytes.into_iter().enumerate() {
+        unsafe { base.add(i).write_volatile(byte) };
+    }
+}
+
+//
+// 감시 상태
+//
+#[derive(Copy, Clone)]
+struct Supervised {
+    handle: TaskHandle,
+    budget_ms: u32,
+    last_checkin: u32,
+}
+
+static WDT: Mutex<RefCell<Option<pac::WDT>>> = Mutex::new(RefCell::new(None));
+static SUPERVISED: Mutex<RefCell<[Option<Supervised>; MAX_SUPERVISED]>> =
+    Mutex::new(RefCell::new([None; MAX_SUPERVISED]));
+/// 정지를 감지해 WDT 리셋을 멈춘 상태
+static TRIPPED: Mutex<RefCell<bool>> = Mutex::new(RefCell::new(false));
+/// 부팅 시 읽어 둔 이전 정지 기록
+static LAST_STALL: Mutex<RefCell<Option<StallRecord>>> = Mutex::new(RefCell::new(None));
+static WATCHDOG_RESET: Mutex<RefCell<bool>> = Mutex::new(RefCell::new(false));
+/// 설정한 WDT 타임아웃 (ms)
+static TIMEOUT_MS: Mutex<RefCell<u32>> = Mutex::new(RefCell::new(0));
+
+/// 감시 등록 실패 원인
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WatchdogError {
+    /// 감시 테이블에 빈 슬롯이 없음
+    TableFull,
+}
+
+/// 하드웨어 WDT 초기화 (System Reset 모드)
+///
+/// 이전 리셋이 WDT에 의한 것이면 `.noinit`에 남은 정지 기록을 `last_stall()`로 옮깁니다.
+pub fn init(wdt: pac::WDT, timeout: WatchdogTimeout) {
+    interrupt::free(|cs| {
+        // 1) 리셋 원인 확인 후 WDRF 클리어 (WDRF가 set이면 WDE를 끌 수 없음)
+        let watchdog_reset = hal::watchdog_take_reset_flag();
+        *WATCHDOG_RESET.borrow(cs).borrow_mut() = watchdog_reset;
+
+        // 2) 이전 정지 기록을 읽고 무효화
+        let stall = PersistedStall::from_bytes(&read_persisted());
+        *LAST_STALL.borrow(cs).borrow_mut() = stall.filter(|_| watchdog_reset).map(|stall| StallRecord {
+            task_slot: stall.task_slot,
+            task_id: stall.task_id,
+            running_slot: Some(stall.running_slot).filter(|&slot| slot != NO_RUNNING_TASK),
+            uptime_ms: stall.uptime_ms,
+        });
+        write_persisted([0; PERSISTED_LEN]);
+
+        // 3) 타임 시퀀스: WDCE|WDE 후 4사이클 안에 새 설정 기록
+        let prescaler = timeout as u8;
+        let config = (1 << WDE) | ((prescaler & 0x08) << (WDP3 - 3)) | (prescaler & 0x07);
+        hal::watchdog_configure(&wdt, config);
+
+        *WDT.borrow(cs).borrow_mut() = Some(wdt);
+        *TIMEOUT_MS.borrow(cs).borrow_mut() = timeout.as_ms();
+    });
+}
+
+/// tickless idle에서 tick 없이 쉴 수 있는 최대 시간 (WDT를 쓰지 않으면 제한 없음)
+///
+/// 쉬는 동안에는 `on_tick()`의 wdr이 없으므로 WDT 타임아웃의 절반으로 제한합니다.
+pub(crate) fn max_idle_ms(cs: CriticalSection) -> Option<u32> {
+    WDT.borrow(cs).borrow().as_ref()?;
+    Some(*TIMEOUT_MS.borrow(cs).borrow() / 2)
+}
+
+/// 직전 리셋이 watchdog에 의한 것이었는지
+pub fn reset_was_watchdog() -> bool {
+    interrupt::free(|cs| *WATCHDOG_RESET.borrow(cs).borrow())
+}
+
+/// 직전 리셋을 일으킨 정지 태스크 기록 (태스크 감시에 의한 리셋이 아니면 None)
+pub fn last_stall() -> Option<StallRecord> {
+    interrupt::free(|cs| *LAST_STALL.borrow(cs).borrow())
+}
+
+/// 태스크를 감시 대상으로 등록합니다. 등록 시점부터 `budget_ms` 안에 check-in해야 합니다.
+///
+/// 이미 등록된 태스크라면 budget만 갱신합니다.
+pub fn supervise(handle: TaskHandle, budget_ms: u32) -> Result<(), WatchdogError> {
+    interrupt::free(|cs| {
+        let now = scheduler::millis();
+        let mut table = SUPERVISED.borrow(cs).borrow_mut();
+        let index = table
+            .iter()
+            .position(|entry| matches!(entry, Some(e) if e.handle == handle))
+            .or_else(|| table.iter().position(|entry| entry.is_none()))
+            .ok_or(WatchdogError::TableFull)?;
+        table[index] = Some(Supervised {
+            handle,
+            budget_ms,
+            last_checkin: now,
+        });
+        Ok(())
+    })
+}
+
+/// 감시 대상에서 제외합니다. (태스크를 제거/일시정지하기 전에 호출)
+pub fn unsupervise(handle: TaskHandle) {
+    interrupt::free(|cs| {
+        for entry in SUPERVISED.borrow(cs).borrow_mut().iter_mut() {
+            if matches!(entry, Some(e) if e.handle == handle) {
+                *entry = None;
+            }
+        }
+    });
+}
+
+/// 현재 실행 중인 태스크의 check-in (태스크 안에서 호출)
+pub fn checkin() {
+    if let Some(handle) = scheduler::current_task() {
+        checkin_task(handle);
+    }
+}
+
+/// 지정한 태스크의 check-in
+pub fn checkin_task(handle: TaskHandle) {
+    interrupt::free(|cs| {
+        let now = scheduler::millis();
+        for entry in SUPERVISED.borrow(cs).borrow_mut().iter_mut().flatten() {
+            if entry.handle == handle {
+                entry.last_checkin = now;
+            }
+        }
+    });
+}
+
+/// 스케줄러 tick에서 ms마다 호출: budget 초과 태스크가 없으면 WDT 리셋
+pub(crate) fn on_tick(cs: CriticalSection, now: u32) {
+    if WDT.borrow(cs).borrow().is_none() || *TRIPPED.borrow(cs).borrow() {
+        return;
+    }
+
+    let stalled = SUPERVISED
+        .borrow(cs)
+        .borrow()
+        .iter()
+        .flatten()
+        .find(|e| now.wrapping_sub(e.last_checkin) > e.budget_ms)
+        .copied();
+
+    match stalled {
+        None => hal::wdr(),
+        Some(entry) => {
+            let record = PersistedStall {
+                task_slot: entry.handle.slot(),
+                task_id: entry.handle.id(),
+                running_slot: scheduler::current_task().map_or(NO_RUNNING_TASK, |h| h.slot()),
+                uptime_ms: now,
+            };
+            write_persisted(record.to_bytes());
+            // 이후로는 wdr을 하지 않으므로 WDT 타임아웃 후 리셋됨
+            *TRIPPED.borrow(cs).borrow_mut() = true;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::hal::sim;
+    use crate::scheduler::tests::{run_until, setup};
+
+    /// 감시 상태 초기화 (`scheduler::tests::setup()`에서 호출)
+    pub(crate) fn reset() {
+        interrupt::free(|cs| {
+            *WDT.borrow(cs).borrow_mut() = None;
+            *SUPERVISED.borrow(cs).borrow_mut() = [None; MAX_SUPERVISED];
+            *TRIPPED.borrow(cs).borrow_mut() = false;
+            *LAST_STALL.borrow(cs).borrow_mut() = None;
+            *WATCHDOG_RESET.borrow(cs).borrow_mut() = false;
+            *TIMEOUT_MS.borrow(cs).borrow_mut() = 0;
+        });
+        write_persisted([0; PERSISTED_LEN]);
+    }
+
+    fn init_watchdog() {
+        init(pac::Peripherals::take().unwrap().WDT, WatchdogTimeout::Ms250);
+    }
+
+    #[test]
+    fn checked_in_tasks_keep_the_watchdog_fed() {
+        let _guard = setup();
+        init_watchdog();
+
+        let handle = scheduler::task_add(checkin, 10).unwrap();
+        supervise(handle, 20).unwrap();
+        let before = sim::wdr_count();
+        run_until(100);
+
+        // 매 ms tick마다 wdr
+        assert_eq!(sim::wdr_count() - before, 100);
+        assert!(!interrupt::free(|cs| *TRIPPED.borrow(cs).borrow()));
+    }
+
+    #[test]
+    fn stalled_task_stops_wdr_and_is_reported_after_reset() {
+        let _guard = setup();
+        init_watchdog();
+        assert!(!reset_was_watchdog());
+        assert_eq!(last_stall(), None);
+
+        let healthy = scheduler::task_add(checkin, 5).unwrap();
+        let stalled = scheduler::task_add(|| {}, 5).unwrap();
+        supervise(healthy, 10).unwrap();
+        supervise(stalled, 10).unwrap();
+
+        run_until(10);
+        let fed = sim::wdr_count();
+        run_until(30);
+        // budget(10ms)을 넘긴 11ms tick부터 wdr 중단
+        assert_eq!(sim::wdr_count(), fed);
+
+        // WDT 리셋 후 재부팅
+        sim::set_watchdog_reset_flag();
+        init_watchdog();
+        assert!(reset_was_watchdog());
+        assert_eq!(
+            last_stall(),
+            Some(StallRecord {
+                task_slot: stalled.slot(),
+                task_id: stalled.id(),
+                running_slot: None,
+                uptime_ms: 11,
+            })
+        );
+
+        // 기록은 한 번만 보고됨
+        sim::set_watchdog_reset_flag();
+        init_watchdog();
+        assert_eq!(last_stall(), None);
+    }
+
+    #[test]
+    fn timeout_selects_wdp_bits() {
+        let _guard = setup();
+
+        let timeouts = [
+            WatchdogTimeout::Ms16,
+            WatchdogTimeout::Ms32,
+            WatchdogTimeout::Ms64,
+            WatchdogTimeout::Ms125,
+            WatchdogTimeout::Ms250,
+            WatchdogTimeout::Ms500,
+            WatchdogTimeout::S1,
+            WatchdogTimeout::S2,
+            WatchdogTimeout::S4,
+            WatchdogTimeout::S8,
+        ];
+        assert_eq!(timeouts.map(WatchdogTimeout::as_ms)[6..], [1024, 2048, 4096, 8192]);
+
+        // WDP2..0은 비트 2..0, WDP3은 비트 5
+        init(pac::Peripherals::take().unwrap().WDT, WatchdogTimeout::S2);
+        assert_eq!(sim::watchdog_config(), (1 << WDE) | 0b111);
+        init(pac::Peripherals::take().unwrap().WDT, WatchdogTimeout::S8);
+        assert_eq!(sim::watchdog_config(), (1 << WDE) | (1 << WDP3) | 0b001);
+        assert_eq!(interrupt::free(max_idle_ms), Some(4096));
+    }
+
+    #[test]
+    fn unsupervised_task_no_longer_stops_wdr() {
+        let _guard = setup();
+        init_watchdog();
+
+        let stalled = scheduler::task_add(|| {}, 5).unwrap();
+        supervise(stalled, 10).unwrap();
+        unsupervise(stalled);
+        let before = sim::wdr_count();
+        run_until(30);
+
+        assert_eq!(sim::wdr_count() - before, 30);
+    }
+
+    #[test]
+    fn stall_record_is_ignored_without_a_watchdog_reset() {
+        let _guard = setup();
+        write_persisted(
+            PersistedStall {
+                task_slot: 1,
+                task_id: 2,
+                running_slot: NO_RUNNING_TASK,
+                uptime_ms: 3,
+            }
+            .to_bytes(),
+        );
+
+        // 전원 투입/외부 리셋이면 남아 있는 기록을 버림
+        init_watchdog();
+        assert!(!reset_was_watchdog());
+        assert_eq!(last_stall(), None);
+        assert_eq!(PersistedStall::from_bytes(&read_persisted()).map(|s| s.uptime_ms), None);
+    }
+}
@@ -0,0 +1,209 @@
+#!/usr/bin/env python3
+#
+# trace 덤프(src/trace.rs) → Chrome trace / Perfetto JSON 변환기
+#
+# 사용법
+#   python3 tools/trace_to_json.py dump.bin > trace.json
+#   python3 tools/trace_to_json.py dump.bin --task 0=led --task 1=uart --isr 16=adc
+#
+# serial 캡처를 그대로 넣으면 됩니다. 덤프는 프레임(sync 0xA5 0x5A + CRC-8)으로 나뉘어
+# 다른 태스크의 출력 사이에 섞여 있으므로, CRC가 맞는 프레임만 골라 덤프 번호별로 다시 이어 붙입니다.
+# (덤프가 여러 개면 모두 이어서 변환)
+# 결과는 chrome://tracing 또는 https://ui.perfetto.dev 에서 열 수 있습니다.
+#
+import argparse
+import json
+import struct
+import sys
+
+MAGIC = b"TRC1"
+HEADER = struct.Struct("<4sHH")
+EVENT = struct.Struct("<IBB")
+
+# 프레임: sync(2) | 덤프 번호 u8 | 덤프 내 위치 u16 | 길이 u8 | 데이터 | CRC-8
+FRAME_SYNC = b"\xa5\x5a"
+FRAME_HEADER = struct.Struct("<2sBHB")
+FRAME_PAYLOAD = 16
+
+TASK_START, TASK_END, ISR_ENTER, ISR_EXIT, MARKER = range(5)
+
+# src/trace.rs의 ISR_* 상수
+ISR_NAMES = {0: "tick", 1: "USART0_UDRE"}
+
+PID = 1
+TID_TASKS = 1
+TID_ISRS = 2
+TID_MARKERS = 3
+
+
+def crc8(data):
+    """CRC-8 (다항식 0x07, 초기값 0)"""
+    crc = 0
+    for byte in data:
+        crc ^= byte
+        for _ in range(8):
+            crc = ((crc << 1) ^ 0x07) & 0xFF if crc & 0x80 else (crc << 1) & 0xFF
+    return crc
+
+
+def parse_frames(data):
+    """CRC가 맞는 프레임 (덤프 번호, 위치, 데이터) 목록. 깨진 프레임과 텍스트는 건너뜀"""
+    frames = []
+    pos = data.find(FRAME_SYNC)
+    while pos >= 0 and pos + FRAME_HEADER.size < len(data):
+        _, seq, offset, length = FRAME_HEADER.unpack_from(data, pos)
+        end = pos + FRAME_HEADER.size + length
+        if length <= FRAME_PAYLOAD and end < len(data) and crc8(data[pos + 2:end]) == data[end]:
+            frames.append((seq, offset, data[pos + FRAME_HEADER.size:end]))
+            pos = data.find(FRAME_SYNC, end + 1)
+        else:
+            # 텍스트 속의 우연한 sync 바이트이거나 깨진 프레임 → 한 바이트 뒤에서 다시 찾음
+            pos = data.find(FRAME_SYNC, pos + 1)
+    return frames
+
+
+def reassemble(frames):
+    """위치 0 프레임에서 새 덤프를 시작하고, 같은 덤프 번호의 프레임을 위치 순서대로 이어 붙임"""
+    dumps = []
+    seq = None
+    broken = None
+    for frame_seq, offset, payload in frames:
+        if offset == 0:
+            seq = frame_seq
+            dumps.append(bytearray())
+        elif frame_seq != seq:
+            if frame_seq != broken:
+                sys.stderr.write("warning: dump {} is missing its start\n".format(frame_seq))
+                broken = frame_seq
+            continue
+        dump = dumps[-1]
+        if offset != len(dump):
+            # 빠진 뒤로는 이벤트 경계를 알 수 없으므로 이 덤프는 여기까지만 사용
+            sys.stderr.write("warning: dump {} lost bytes {}..{} (corrupted frame)\n"
+                             .format(seq, len(dump), offset))
+            seq = None
+            broken = frame_seq
+            continue
+        dump.extend(payload)
+    return dumps
+
+
+def parse_dumps(data):
+    """(이벤트 목록, 잃은 이벤트 수) 를 덤프마다 반환"""
+    dumps = []
+    for dump in reassemble(parse_frames(data)):
+        if len(dump) < HEADER.size or dump[:4] != MAGIC:
+            sys.stderr.write("warning: skipping a dump without a 'TRC1' header\n")
+            continue
+        _, count, lost = HEADER.unpack_from(dump, 0)
+        available = (len(dump) - HEADER.size) // EVENT.size
+        if available < count:
+            sys.stderr.write("warning: truncated dump ({} of {} events)\n".format(available, count))
+            count = available
+        events = [EVENT.unpack_from(dump, HEADER.size + i * EVENT.size) for i in range(count)]
+        dumps.append((events, lost))
+    return dumps
+
+
+def unwrap_timestamps(events, offset):
+    """u32 us 타임스탬프(약 71분마다 한 바퀴)를 단조 증가하는 값으로 펼침"""
+    unwrapped = []
+    prev = None
+    for ts, kind, ident in events:
+        if prev is not None and ts < prev and prev - ts > 1 << 31:
+            offset += 1 << 32
+        prev = ts
+        unwrapped.append((ts + offset, kind, ident))
+    return unwrapped, offset
+
+
+def to_trace_events(events, task_names, isr_names, marker_names):
+    out = []
+    # 링버퍼가 덮어써져 시작(B)이 사라진 구간의 종료(E)는 버림
+    open_tasks = set()
+    open_isrs = set()
+    for ts, kind, ident in events:
+        if kind in (TASK_START, TASK_END):
+            name = task_names.get(ident, "task {}".format(ident))
+            if kind == TASK_START:
+                open_tasks.add(ident)
+            elif ident in open_tasks:
+                open_tasks.discard(ident)
+            else:
+                continue
+            out.append({"name": name, "cat": "task", "ph": "B" if kind == TASK_START else "E",
+                        "ts": ts, "pid": PID, "tid": TID_TASKS, "args": {"slot": ident}})
+        elif kind in (ISR_ENTER, ISR_EXIT):
+            name = isr_names.get(ident, "isr {}".format(ident))
+            if kind == ISR_ENTER:
+                open_isrs.add(ident)
+            elif ident in open_isrs:
+                open_isrs.discard(ident)
+            else:
+                continue
+            out.append({"name": name, "cat": "isr", "ph": "B" if kind == ISR_ENTER else "E",
+                        "ts": ts, "pid": PID, "tid": TID_ISRS, "args": {"id": ident}})
+        elif kind == MARKER:
+            name = marker_names.get(ident, "marker {}".format(ident))
+            out.append({"name": name, "cat": "marker", "ph": "i", "s": "t",
+                        "ts": ts, "pid": PID, "tid": TID_MARKERS, "args": {"id": ident}})
+        else:
+            sys.stderr.write("warning: unknown event kind {} at {} us\n".format(kind, ts))
+    return out
+
+
+def parse_names(pairs):
+    names = {}
+    for pair in pairs or []:
+        ident, _, name = pair.partition("=")
+        names[int(ident, 0)] = name
+    return names
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Convert an AVR trace dump to Chrome trace JSON")
+    parser.add_argument("input", help="binary dump or raw serial capture ('-' for stdin)")
+    parser.add_argument("-o", "--output", help="output file (default: stdout)")
+    parser.add_argument("--task", action="append", metavar="SLOT=NAME", help="task slot name")
+    parser.add_argument("--isr", action="append", metavar="ID=NAME", help="ISR name")
+    parser.add_argument("--marker", action="append", metavar="ID=NAME", help="marker name")
+    args = parser.parse_args()
+
+    if args.input == "-":
+        data = sys.stdin.buffer.read()
+    else:
+        with open(args.input, "rb") as f:
+            data = f.read()
+
+    dumps = parse_dumps(data)
+    if not dumps:
+        sys.exit("error: no trace dump frames found")
+
+    task_names = parse_names(args.task)
+    isr_names = dict(ISR_NAMES)
+    isr_names.update(parse_names(args.isr))
+    marker_names = parse_names(args.marker)
+
+    trace_events = [
+        {"ph": "M", "name": "process_name", "pid": PID, "args": {"name": "ATmega2560"}},
+        {"ph": "M", "name": "thread_name", "pid": PID, "tid": TID_TASKS, "args": {"name": "tasks"}},
+        {"ph": "M", "name": "thread_name", "pid": PID, "tid": TID_ISRS, "args": {"name": "ISRs"}},
+        {"ph": "M", "name": "thread_name", "pid": PID, "tid": TID_MARKERS, "args": {"name": "markers"}},
+    ]
+    offset = 0
+    for events, lost in dumps:
+        if lost:
+            sys.stderr.write("warning: {} events were overwritten before the dump\n".format(lost))
+        events, offset = unwrap_timestamps(events, offset)
+        trace_events.extend(to_trace_events(events, task_names, isr_names, marker_names))
+
+    result = json.dumps({"traceEvents": trace_events, "displayTimeUnit": "ns"}, indent=1)
+    if args.output:
+        with open(args.output, "w") as f:
+            f.write(result)
+    else:
+        print(result)
+
+
+if __name__ == "__main__":
+    main()